@@ -0,0 +1,120 @@
+use crate::{
+    hittable::Sphere,
+    ray::Ray,
+    texture::{ImageTexture, Texture},
+    vec3::{self, Color},
+};
+use anyhow::Result;
+use std::{fmt::Debug, sync::Arc};
+
+pub trait Background: Debug + Send + Sync {
+    fn value(&self, r: &Ray) -> Color;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SolidBackground {
+    color: Color,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GradientBackground {
+    bottom: Color,
+    top: Color,
+}
+
+#[derive(Debug, Clone)]
+pub struct EnvironmentMap {
+    tex: Arc<dyn Texture>,
+}
+
+impl SolidBackground {
+    pub fn new(color: Color) -> Self {
+        Self { color }
+    }
+}
+
+impl Background for SolidBackground {
+    fn value(&self, _r: &Ray) -> Color {
+        self.color
+    }
+}
+
+impl GradientBackground {
+    pub fn new(bottom: Color, top: Color) -> Self {
+        Self { bottom, top }
+    }
+}
+
+impl Default for GradientBackground {
+    fn default() -> Self {
+        Self::new(Color::new(1_f64, 1_f64, 1_f64), Color::new(0.5, 0.7, 1_f64))
+    }
+}
+
+impl Background for GradientBackground {
+    fn value(&self, r: &Ray) -> Color {
+        let unit_direction = vec3::unit_vector(r.direction());
+        let a = 0.5 * (unit_direction.y() + 1_f64);
+
+        (1_f64 - a) * self.bottom + a * self.top
+    }
+}
+
+impl EnvironmentMap {
+    pub fn new(filename: &str) -> Result<Self> {
+        Ok(Self {
+            tex: Arc::new(ImageTexture::new(filename)?),
+        })
+    }
+
+    pub fn from(tex: Arc<dyn Texture>) -> Self {
+        Self { tex }
+    }
+}
+
+impl Background for EnvironmentMap {
+    fn value(&self, r: &Ray) -> Color {
+        let unit_direction = vec3::unit_vector(r.direction());
+        let uv = Sphere::get_sphere_uv(&unit_direction);
+
+        self.tex.value(uv[0], uv[1], &unit_direction)
+    }
+}
+
+#[test]
+fn test_gradient_background_matches_sky() {
+    use crate::vec3::{Point3, Vec3};
+
+    let background = GradientBackground::default();
+    let up = Ray::new(Point3::zeroes(), Vec3::new(0_f64, 2_f64, 0_f64), 0_f64);
+    let down = Ray::new(Point3::zeroes(), Vec3::new(0_f64, -2_f64, 0_f64), 0_f64);
+
+    assert_eq!(Color::new(0.5, 0.7, 1_f64), background.value(&up));
+    assert_eq!(Color::new(1_f64, 1_f64, 1_f64), background.value(&down));
+}
+
+#[test]
+fn test_environment_map_equirectangular_lookup() {
+    use crate::{
+        texture::SolidColor,
+        vec3::{Point3, Vec3},
+    };
+
+    #[derive(Debug)]
+    struct UvTexture;
+
+    impl Texture for UvTexture {
+        fn value(&self, u: f64, v: f64, _p: &Color) -> Color {
+            Color::new(u, v, 0_f64)
+        }
+    }
+
+    let env = EnvironmentMap::from(Arc::new(UvTexture));
+    let solid = EnvironmentMap::from(Arc::new(SolidColor::from_rgb(0.1, 0.2, 0.3)));
+    let up = Ray::new(Point3::zeroes(), Vec3::new(0_f64, 1_f64, 0_f64), 0_f64);
+    let along_x = Ray::new(Point3::zeroes(), Vec3::new(3_f64, 0_f64, 0_f64), 0_f64);
+
+    assert_eq!(1_f64, env.value(&up)[1]);
+    assert_eq!(Color::new(0.5, 0.5, 0_f64), env.value(&along_x));
+    assert_eq!(Color::new(0.1, 0.2, 0.3), solid.value(&up));
+}
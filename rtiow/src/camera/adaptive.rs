@@ -0,0 +1,228 @@
+use super::aov::AovSamples;
+use crate::{
+    vec3::{self, Color},
+    INFINITY,
+};
+use serde::Deserialize;
+
+/// Renders in passes and stops sampling a pixel once the estimated error of
+/// its mean luminance is below `threshold`, so flat regions take few samples
+/// and noisy ones up to the camera's `samples_per_pixel`.
+///
+/// The error is the standard error of the mean luminance relative to the
+/// mean itself, estimated from the variance of the samples taken so far. A
+/// pixel only stops once it and its eight neighbors are below the threshold,
+/// which keeps pixels sampling whose few samples agreed by chance, such as
+/// ones that all missed a small light.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AdaptiveSampling {
+    pub threshold: f64,
+    /// Samples every unconverged pixel takes per pass. A pixel is never
+    /// checked before its first pass, so this is also the minimum.
+    pub pass_samples: u32,
+}
+
+/// Running sums of the samples of one pixel.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct PixelEstimate {
+    color: Color,
+    aov_samples: AovSamples,
+    samples: u32,
+    luminance: f64,
+    luminance_squared: f64,
+    converged: bool,
+}
+
+impl Default for AdaptiveSampling {
+    fn default() -> Self {
+        Self {
+            threshold: 0.02,
+            pass_samples: 16,
+        }
+    }
+}
+
+impl AdaptiveSampling {
+    /// Mean luminance below which the error is taken relative to this floor
+    /// instead, so pixels close to black can converge.
+    const MIN_LUMINANCE: f64 = 1e-3;
+
+    pub fn new(threshold: f64) -> Self {
+        Self {
+            threshold,
+            ..Self::default()
+        }
+    }
+
+    pub fn pass_samples(mut self, pass_samples: u32) -> Self {
+        self.pass_samples = pass_samples;
+        self
+    }
+
+    /// Number of samples of each pass when at most `max_samples` are taken
+    /// per pixel.
+    pub(super) fn passes(&self, max_samples: u32) -> Vec<u32> {
+        let pass_samples = self.pass_samples.clamp(1, max_samples);
+        let mut passes = vec![pass_samples; (max_samples / pass_samples) as usize];
+        let remainder = max_samples % pass_samples;
+
+        if remainder > 0 {
+            passes.push(remainder);
+        }
+
+        passes
+    }
+
+    /// Marks the pixels of an image `width` pixels wide whose neighborhood
+    /// is below the threshold as converged.
+    pub(super) fn update(&self, pixels: &mut [PixelEstimate], width: usize) {
+        let height = pixels.len() / width;
+        let errors = pixels
+            .iter()
+            .map(|estimate| self.error(estimate))
+            .collect::<Vec<f64>>();
+
+        for (index, estimate) in pixels.iter_mut().enumerate() {
+            let (x, y) = (index % width, index / width);
+            let error = (y.saturating_sub(1)..(y + 2).min(height))
+                .flat_map(|qy| {
+                    (x.saturating_sub(1)..(x + 2).min(width)).map(move |qx| qy * width + qx)
+                })
+                .map(|q| errors[q])
+                .fold(0_f64, f64::max);
+
+            estimate.converged = error < self.threshold;
+        }
+    }
+
+    fn error(&self, estimate: &PixelEstimate) -> f64 {
+        if estimate.samples < 2 {
+            return INFINITY;
+        }
+
+        let n = estimate.samples as f64;
+        let mean = estimate.luminance / n;
+        let variance = ((estimate.luminance_squared - n * mean * mean) / (n - 1_f64)).max(0_f64);
+        let standard_error = (variance / n).sqrt();
+
+        standard_error / mean.max(Self::MIN_LUMINANCE)
+    }
+}
+
+impl PixelEstimate {
+    pub fn new() -> Self {
+        Self {
+            color: Color::zeroes(),
+            aov_samples: AovSamples::miss(),
+            samples: 0,
+            luminance: 0_f64,
+            luminance_squared: 0_f64,
+            converged: false,
+        }
+    }
+
+    pub fn add(&mut self, color: Color, aov_samples: AovSamples) {
+        let luminance = vec3::luminance(&color);
+
+        self.color += color;
+        self.aov_samples = self.aov_samples.merge(aov_samples);
+        self.samples += 1;
+        self.luminance += luminance;
+        self.luminance_squared += luminance * luminance;
+    }
+
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    /// The mean color, the sample count and the AOV samples.
+    pub fn finish(&self) -> (Color, u32, AovSamples) {
+        (
+            (1_f64 / self.samples as f64) * self.color,
+            self.samples,
+            self.aov_samples,
+        )
+    }
+}
+
+#[test]
+fn test_adaptive_passes() {
+    let adaptive = AdaptiveSampling::new(0.05);
+
+    assert_eq!(vec![16, 16, 8], adaptive.passes(40));
+    assert_eq!(vec![4], adaptive.pass_samples(8).passes(4));
+    assert_eq!(vec![1, 1], adaptive.pass_samples(0).passes(2));
+}
+
+#[test]
+fn test_adaptive_convergence() {
+    let adaptive = AdaptiveSampling::new(0.05);
+    let mut flat = PixelEstimate::new();
+    let mut noisy = PixelEstimate::new();
+    let mut black = PixelEstimate::new();
+
+    for s in 0..16 {
+        let v = if s % 2 == 0 { 0_f64 } else { 2_f64 };
+
+        flat.add(Color::new(0.5, 0.5, 0.5), AovSamples::miss());
+        noisy.add(Color::new(v, v, v), AovSamples::miss());
+        black.add(Color::zeroes(), AovSamples::miss());
+    }
+
+    assert!(adaptive.error(&flat) < 0.05);
+    assert!(adaptive.error(&noisy) > 0.05);
+    assert_eq!(0_f64, adaptive.error(&black));
+    assert_eq!(INFINITY, adaptive.error(&PixelEstimate::new()));
+
+    // A black pixel next to a noisy one keeps sampling
+    let mut pixels = [noisy, black, black, black];
+
+    adaptive.update(&mut pixels, 4);
+
+    assert_eq!(
+        vec![false, false, true, true],
+        pixels.iter().map(|p| p.converged()).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_adaptive_render_spends_samples_on_noisy_pixels() {
+    use crate::{
+        background::SolidBackground,
+        camera::CameraBuilder,
+        hittable::{self, DiffuseLight, Sphere},
+        vec3::Point3,
+    };
+    use std::sync::Arc;
+
+    let mut world = hittable::test_world();
+
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0_f64, 1.6, -3_f64),
+        0.3,
+        Arc::new(DiffuseLight::new(Color::new(8_f64, 8_f64, 8_f64))),
+    )));
+
+    let framebuffer = CameraBuilder::new()
+        .image_width(16)
+        .samples_per_pixel(128)
+        .seed(5)
+        .background(Arc::new(SolidBackground::new(Color::zeroes())))
+        .adaptive(Some(AdaptiveSampling::new(0.02)))
+        .build()
+        .expect("camera is valid")
+        .render(&world);
+
+    // The top row only sees the black background; the bottom row the ground
+    // lit by a small light
+    for x in 0..16 {
+        assert_eq!(16, framebuffer.samples(x, 0));
+    }
+    assert!((0..16).any(|x| framebuffer.samples(x, 15) > 16));
+    assert!(framebuffer.sample_counts().iter().all(|n| *n <= 128));
+}
@@ -0,0 +1,312 @@
+use crate::{
+    framebuffer::{Framebuffer, Layer},
+    hittable::{HitRecord, Material},
+    ray::Ray,
+    vec3::{self, Color, Vec3},
+    INFINITY,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Auxiliary per-pixel output recorded from the first hit of each camera
+/// ray and stored as a framebuffer layer named after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Aov {
+    /// Distance along the view direction; infinite where nothing was hit.
+    Depth,
+    /// Unit shading normal facing the camera.
+    Normal,
+    /// Base color from `Material::albedo`.
+    Albedo,
+    /// Surface texture coordinates.
+    Uv,
+    /// Dense material index: 0 where nothing was hit, then 1, 2, ... in the
+    /// order materials first appear scanning from the top-left pixel.
+    MaterialId,
+}
+
+/// First-hit data of the samples of one pixel, summed over the samples that
+/// hit something.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct AovSamples {
+    hits: u32,
+    depth: f64,
+    normal: Vec3,
+    albedo: Color,
+    uv: [f64; 2],
+    /// Address of the material of the first sample that hit, identifying it
+    /// until `layers` remaps it.
+    material: usize,
+}
+
+impl Aov {
+    pub const ALL: [Self; 5] = [
+        Self::Depth,
+        Self::Normal,
+        Self::Albedo,
+        Self::Uv,
+        Self::MaterialId,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Depth => "depth",
+            Self::Normal => "normal",
+            Self::Albedo => "albedo",
+            Self::Uv => "uv",
+            Self::MaterialId => "material_id",
+        }
+    }
+
+    /// The layer of this AOV in `framebuffer` as an image for 8- and 16-bit
+    /// formats, which clamp to `[0, 1]`: depth is divided by its largest
+    /// finite value with misses white, normals are mapped from `[-1, 1]`
+    /// and material ids are divided by the largest id. Every AOV but the
+    /// albedo is data rather than color, so it is stored such that sRGB
+    /// encoding writes the values themselves.
+    pub fn display_image(&self, framebuffer: &Framebuffer) -> Option<Framebuffer> {
+        let image = framebuffer.layer_image(self.name())?;
+
+        if *self == Self::Albedo {
+            return Some(image);
+        }
+
+        let max = image
+            .pixels()
+            .iter()
+            .map(|color| color.x())
+            .filter(|value| value.is_finite())
+            .fold(0_f64, f64::max);
+        let remap = |value: f64| match self {
+            Self::Depth if !value.is_finite() => 1_f64,
+            Self::Depth | Self::MaterialId if max > 0_f64 => value / max,
+            Self::Normal => 0.5 * value + 0.5,
+            _ => value,
+        };
+        let pixels = image
+            .pixels()
+            .iter()
+            .zip(image.sample_counts())
+            .map(|(color, samples)| {
+                let color = Color::new(
+                    vec3::srgb_to_linear(remap(color.x())),
+                    vec3::srgb_to_linear(remap(color.y())),
+                    vec3::srgb_to_linear(remap(color.z())),
+                );
+
+                (color, *samples)
+            })
+            .collect();
+
+        Some(Framebuffer::from_pixels(
+            image.width(),
+            image.height(),
+            pixels,
+        ))
+    }
+
+    pub fn channels(&self) -> &'static [&'static str] {
+        match self {
+            Self::Depth => &["Z"],
+            Self::Normal => &["X", "Y", "Z"],
+            Self::Albedo => &["R", "G", "B"],
+            Self::Uv => &["U", "V"],
+            Self::MaterialId => &["id"],
+        }
+    }
+}
+
+impl AovSamples {
+    pub fn miss() -> Self {
+        Self {
+            hits: 0,
+            depth: 0_f64,
+            normal: Vec3::zeroes(),
+            albedo: Color::zeroes(),
+            uv: [0_f64; 2],
+            material: 0,
+        }
+    }
+
+    pub fn hit(r: &Ray, rec: &HitRecord, forward: &Vec3) -> Self {
+        Self {
+            hits: 1,
+            depth: vec3::dot(&(rec.p - *r.origin()), forward),
+            normal: rec.normal,
+            albedo: rec.mat.albedo(rec),
+            uv: [rec.u, rec.v],
+            material: rec.mat as *const dyn Material as *const () as usize,
+        }
+    }
+
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            hits: self.hits + other.hits,
+            depth: self.depth + other.depth,
+            normal: self.normal + other.normal,
+            albedo: self.albedo + other.albedo,
+            uv: [self.uv[0] + other.uv[0], self.uv[1] + other.uv[1]],
+            material: if self.material != 0 {
+                self.material
+            } else {
+                other.material
+            },
+        }
+    }
+
+    /// Attaches a layer for each of `aovs` to `framebuffer`, averaging the
+    /// samples of every pixel.
+    pub fn layers(framebuffer: &mut Framebuffer, aovs: &[Aov], pixels: &[Self]) {
+        let mut materials = HashMap::new();
+        let material_ids = pixels
+            .iter()
+            .map(|pixel| match pixel.material {
+                0 => 0,
+                material => {
+                    let next = materials.len() + 1;
+
+                    *materials.entry(material).or_insert(next)
+                }
+            })
+            .collect::<Vec<usize>>();
+
+        for aov in aovs {
+            let data = pixels
+                .iter()
+                .zip(material_ids.iter())
+                .flat_map(|(pixel, id)| pixel.average(*aov, *id))
+                .collect();
+
+            framebuffer.add_layer(aov.name(), Layer::new(aov.channels(), data));
+        }
+    }
+
+    fn average(&self, aov: Aov, material_id: usize) -> Vec<f64> {
+        if self.hits == 0 {
+            return match aov {
+                Aov::Depth => vec![INFINITY],
+                _ => vec![0_f64; aov.channels().len()],
+            };
+        }
+
+        let scale = 1_f64 / self.hits as f64;
+
+        match aov {
+            Aov::Depth => vec![scale * self.depth],
+            Aov::Normal => {
+                let normal = if self.normal.near_zero() {
+                    self.normal
+                } else {
+                    vec3::unit_vector(&self.normal)
+                };
+
+                vec![normal.x(), normal.y(), normal.z()]
+            }
+            Aov::Albedo => {
+                let albedo = scale * self.albedo;
+
+                vec![albedo.x(), albedo.y(), albedo.z()]
+            }
+            Aov::Uv => vec![scale * self.uv[0], scale * self.uv[1]],
+            Aov::MaterialId => vec![material_id as f64],
+        }
+    }
+}
+
+#[test]
+fn test_camera_renders_aov_layers() {
+    use crate::{camera::CameraBuilder, hittable};
+
+    let world = hittable::test_world();
+
+    let camera = CameraBuilder::new()
+        .image_width(16)
+        .samples_per_pixel(4)
+        .max_depth(2)
+        .aovs(&Aov::ALL)
+        .build()
+        .expect("camera is valid");
+    let framebuffer = camera.render(&world);
+    let layer = |aov: Aov, x: usize, y: usize| {
+        framebuffer
+            .layer(aov.name())
+            .expect("aov layer exists")
+            .pixel(y * 16 + x)
+            .to_vec()
+    };
+
+    let depth = layer(Aov::Depth, 7, 7)[0];
+    let normal = layer(Aov::Normal, 7, 7);
+
+    assert!((2_f64..2.1).contains(&depth), "{}", depth);
+    let length = normal.iter().map(|e| e * e).sum::<f64>().sqrt();
+
+    assert!(
+        (length - 1_f64).abs() < 1e-9 && normal[2] > 0.9,
+        "{:?}",
+        normal
+    );
+    assert_eq!(vec![0.2, 0.4, 0.6], layer(Aov::Albedo, 7, 7));
+    assert_eq!(2, layer(Aov::Uv, 7, 7).len());
+    assert_eq!(vec![INFINITY], layer(Aov::Depth, 0, 0));
+    assert_eq!(vec![0_f64], layer(Aov::MaterialId, 0, 0));
+    assert_eq!(vec![1_f64], layer(Aov::MaterialId, 7, 7));
+    assert_eq!(vec![2_f64], layer(Aov::MaterialId, 8, 15));
+}
+
+#[test]
+fn test_aov_display_image() {
+    let mut framebuffer = Framebuffer::new(3, 1);
+
+    framebuffer.add_layer(
+        Aov::Depth.name(),
+        Layer::new(Aov::Depth.channels(), vec![1_f64, 4_f64, INFINITY]),
+    );
+    framebuffer.add_layer(
+        Aov::Normal.name(),
+        Layer::new(
+            Aov::Normal.channels(),
+            vec![
+                0_f64, 0_f64, 1_f64, -1_f64, 0_f64, 0_f64, 0_f64, 0_f64, 0_f64,
+            ],
+        ),
+    );
+    framebuffer.add_layer(
+        Aov::MaterialId.name(),
+        Layer::new(Aov::MaterialId.channels(), vec![0_f64, 1_f64, 2_f64]),
+    );
+
+    // The values written by an 8-bit writer, before quantization
+    let displayed = |aov: Aov| {
+        let image = aov.display_image(&framebuffer).expect("layer exists");
+
+        (0..3)
+            .map(|x| {
+                let color = image.pixel(x, 0);
+
+                [color.x(), color.y(), color.z()].map(vec3::linear_to_srgb)
+            })
+            .collect::<Vec<[f64; 3]>>()
+    };
+    let close = |a: Vec<[f64; 3]>, b: Vec<[f64; 3]>| {
+        a.iter()
+            .flatten()
+            .zip(b.iter().flatten())
+            .all(|(a, b)| (a - b).abs() < 1e-9)
+    };
+
+    assert!(close(
+        vec![[0.25; 3], [1_f64; 3], [1_f64; 3]],
+        displayed(Aov::Depth)
+    ));
+    assert!(close(
+        vec![[0.5, 0.5, 1_f64], [0_f64, 0.5, 0.5], [0.5; 3]],
+        displayed(Aov::Normal)
+    ));
+    assert!(close(
+        vec![[0_f64; 3], [0.5; 3], [1_f64; 3]],
+        displayed(Aov::MaterialId)
+    ));
+    assert!(Aov::Albedo.display_image(&framebuffer).is_none());
+}
@@ -0,0 +1,341 @@
+use super::{AdaptiveSampling, Aov, Camera};
+use crate::{
+    background::{Background, GradientBackground},
+    denoise::Denoiser,
+    error::RTError,
+    sampler::SamplerKind,
+    utils,
+    vec3::{self, Point3, Vec3},
+};
+use anyhow::{bail, Result};
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// Collects the camera parameters and validates them in `build`. Parameters
+/// that are never set keep the defaults used in the book.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CameraBuilder {
+    aspect_ratio: f64,
+    image_width: i32,
+    samples_per_pixel: i32,
+    max_depth: i32,
+    vfov: f64,
+    look_from: Point3,
+    look_at: Point3,
+    vup: Vec3,
+    defocus_angle: f64,
+    focus_distance: f64,
+    aovs: Vec<Aov>,
+    denoise: Option<Denoiser>,
+    seed: Option<u64>,
+    sampler: SamplerKind,
+    adaptive: Option<AdaptiveSampling>,
+    #[serde(skip)]
+    background: Arc<dyn Background>,
+}
+
+impl Default for CameraBuilder {
+    fn default() -> Self {
+        Self {
+            aspect_ratio: 1_f64,
+            image_width: 100,
+            samples_per_pixel: 10,
+            max_depth: 10,
+            vfov: 90_f64,
+            look_from: Point3::zeroes(),
+            look_at: Point3::new(0_f64, 0_f64, -1_f64),
+            vup: Vec3::new(0_f64, 1_f64, 0_f64),
+            defocus_angle: 0_f64,
+            focus_distance: 1e1,
+            aovs: Vec::new(),
+            denoise: None,
+            seed: None,
+            sampler: SamplerKind::default(),
+            adaptive: None,
+            background: Arc::new(GradientBackground::default()),
+        }
+    }
+}
+
+impl CameraBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn aspect_ratio(mut self, aspect_ratio: f64) -> Self {
+        self.aspect_ratio = aspect_ratio;
+        self
+    }
+
+    pub fn image_width(mut self, image_width: i32) -> Self {
+        self.image_width = image_width;
+        self
+    }
+
+    pub fn samples_per_pixel(mut self, samples_per_pixel: i32) -> Self {
+        self.samples_per_pixel = samples_per_pixel;
+        self
+    }
+
+    pub fn max_depth(mut self, max_depth: i32) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Vertical field of view in degrees.
+    pub fn vfov(mut self, vfov: f64) -> Self {
+        self.vfov = vfov;
+        self
+    }
+
+    pub fn look_from(mut self, look_from: Point3) -> Self {
+        self.look_from = look_from;
+        self
+    }
+
+    pub fn look_at(mut self, look_at: Point3) -> Self {
+        self.look_at = look_at;
+        self
+    }
+
+    pub fn vup(mut self, vup: Vec3) -> Self {
+        self.vup = vup;
+        self
+    }
+
+    /// Angle in degrees of the cone through each pixel with its apex at the
+    /// focus plane; zero disables defocus blur.
+    pub fn defocus_angle(mut self, defocus_angle: f64) -> Self {
+        self.defocus_angle = defocus_angle;
+        self
+    }
+
+    pub fn focus_distance(mut self, focus_distance: f64) -> Self {
+        self.focus_distance = focus_distance;
+        self
+    }
+
+    /// Auxiliary outputs the camera records as framebuffer layers.
+    pub fn aovs(mut self, aovs: &[Aov]) -> Self {
+        self.aovs = aovs.to_vec();
+        self.aovs.sort();
+        self.aovs.dedup();
+        self
+    }
+
+    /// Filters every render with `denoiser`, recording the AOVs it needs
+    /// even when they are not requested; `None` keeps the raw render.
+    pub fn denoise(mut self, denoiser: Option<Denoiser>) -> Self {
+        self.denoise = denoiser;
+        self
+    }
+
+    /// Seed of the sampler and its scrambling; renders with the same seed
+    /// are identical. Without one, `build` draws a fresh seed with
+    /// `utils::random_seed`.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn sampler(mut self, sampler: SamplerKind) -> Self {
+        self.sampler = sampler;
+        self
+    }
+
+    /// Samples pixels adaptively, with `samples_per_pixel` as the maximum;
+    /// `None` takes `samples_per_pixel` samples everywhere.
+    pub fn adaptive(mut self, adaptive: Option<AdaptiveSampling>) -> Self {
+        self.adaptive = adaptive;
+        self
+    }
+
+    pub fn background(mut self, background: Arc<dyn Background>) -> Self {
+        self.background = background;
+        self
+    }
+
+    pub fn build(&self) -> Result<Camera> {
+        self.validate()?;
+
+        let image_height = ((self.image_width as f64 / self.aspect_ratio) as i32).max(1);
+        let center = self.look_from;
+
+        let theta = utils::degrees_to_radians(self.vfov);
+        let h = (theta / 2_f64).tan();
+        let viewport_height = 2_f64 * h * self.focus_distance;
+        let viewport_width = viewport_height * (self.image_width as f64 / image_height as f64);
+
+        let w = vec3::unit_vector(&(self.look_from - self.look_at));
+        let u = vec3::unit_vector(&vec3::cross(&self.vup, &w));
+        let v = vec3::cross(&w, &u);
+
+        let viewport_u = viewport_width * u;
+        let viewport_v = viewport_height * -v;
+
+        let pixel_delta = [
+            viewport_u / self.image_width as f64,
+            viewport_v / image_height as f64,
+        ];
+
+        let viewport_upper_left =
+            center - (self.focus_distance * w) - (viewport_u + viewport_v) / 2_f64;
+        let pixel00_loc = viewport_upper_left + 0.5 * (pixel_delta.iter().sum::<Point3>());
+
+        let defocus_radius =
+            self.focus_distance * utils::degrees_to_radians(self.defocus_angle / 2_f64).tan();
+        let defocus_disk = [u * defocus_radius, v * defocus_radius];
+
+        Ok(Camera {
+            image_size: [self.image_width, image_height],
+            center,
+            pixel_delta,
+            pixel00_loc,
+            samples_per_pixel: self.samples_per_pixel,
+            max_depth: self.max_depth,
+            defocus_angle: self.defocus_angle,
+            defocus_disk,
+            forward: -w,
+            background: self.background.clone(),
+            aovs: self.aovs.clone(),
+            denoiser: self.denoise,
+            sampler: self.sampler.build(
+                self.samples_per_pixel as u32,
+                self.seed.unwrap_or_else(utils::random_seed),
+            ),
+            adaptive: self.adaptive,
+        })
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !(self.aspect_ratio.is_finite() && self.aspect_ratio > 0_f64) {
+            bail!(RTError::InvalidCamera(format!(
+                "aspect_ratio must be positive, found {}",
+                self.aspect_ratio
+            )));
+        }
+        if self.image_width < 1 {
+            bail!(RTError::InvalidCamera(format!(
+                "image_width must be at least 1, found {}",
+                self.image_width
+            )));
+        }
+        if self.samples_per_pixel < 1 {
+            bail!(RTError::InvalidCamera(format!(
+                "samples_per_pixel must be at least 1, found {}",
+                self.samples_per_pixel
+            )));
+        }
+        if self.max_depth < 1 {
+            bail!(RTError::InvalidCamera(format!(
+                "max_depth must be at least 1, found {}",
+                self.max_depth
+            )));
+        }
+        if !(self.vfov > 0_f64 && self.vfov < 180_f64) {
+            bail!(RTError::InvalidCamera(format!(
+                "vfov must be between 0 and 180 degrees, found {}",
+                self.vfov
+            )));
+        }
+        if !(self.defocus_angle >= 0_f64 && self.defocus_angle < 180_f64) {
+            bail!(RTError::InvalidCamera(format!(
+                "defocus_angle must be between 0 and 180 degrees, found {}",
+                self.defocus_angle
+            )));
+        }
+        if !(self.focus_distance.is_finite() && self.focus_distance > 0_f64) {
+            bail!(RTError::InvalidCamera(format!(
+                "focus_distance must be positive, found {}",
+                self.focus_distance
+            )));
+        }
+
+        if let Some(denoiser) = &self.denoise {
+            for (name, sigma) in [
+                ("sigma_color", denoiser.sigma_color),
+                ("sigma_normal", denoiser.sigma_normal),
+                ("sigma_albedo", denoiser.sigma_albedo),
+                ("sigma_depth", denoiser.sigma_depth),
+            ] {
+                if !(sigma.is_finite() && sigma > 0_f64) {
+                    bail!(RTError::InvalidCamera(format!(
+                        "denoise.{} must be positive, found {}",
+                        name, sigma
+                    )));
+                }
+            }
+        }
+
+        if let Some(adaptive) = &self.adaptive {
+            if !(adaptive.threshold.is_finite() && adaptive.threshold > 0_f64) {
+                bail!(RTError::InvalidCamera(format!(
+                    "adaptive.threshold must be positive, found {}",
+                    adaptive.threshold
+                )));
+            }
+            if adaptive.pass_samples < 1 {
+                bail!(RTError::InvalidCamera(
+                    "adaptive.pass_samples must be at least 1".into()
+                ));
+            }
+        }
+
+        let view = self.look_from - self.look_at;
+
+        if view.near_zero() {
+            bail!(RTError::InvalidCamera(
+                "look_from and look_at must differ".into()
+            ));
+        }
+        if vec3::cross(&self.vup, &vec3::unit_vector(&view)).near_zero() {
+            bail!(RTError::InvalidCamera(
+                "vup must not be parallel to the view direction".into()
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_camera_builder_defaults() {
+    let camera = CameraBuilder::new()
+        .image_width(400)
+        .aspect_ratio(2_f64)
+        .build()
+        .expect("defaults are valid");
+
+    assert_eq!([400, 200], camera.image_size());
+    assert_eq!(10, camera.samples_per_pixel);
+}
+
+#[test]
+fn test_camera_builder_rejects_invalid_parameters() {
+    let invalid = [
+        CameraBuilder::new().samples_per_pixel(0),
+        CameraBuilder::new().image_width(0),
+        CameraBuilder::new().aspect_ratio(0_f64),
+        CameraBuilder::new().vfov(180_f64),
+        CameraBuilder::new().focus_distance(0_f64),
+        CameraBuilder::new().look_at(Point3::zeroes()),
+        CameraBuilder::new().vup(Vec3::new(0_f64, 0_f64, 2_f64)),
+        CameraBuilder::new().denoise(Some(Denoiser::new().sigma_normal(0_f64))),
+        CameraBuilder::new().adaptive(Some(AdaptiveSampling::new(-1_f64))),
+    ];
+
+    for builder in invalid.iter() {
+        let err = builder.build().expect_err("configuration is invalid");
+
+        assert!(
+            matches!(
+                err.downcast_ref::<RTError>(),
+                Some(RTError::InvalidCamera(_))
+            ),
+            "{:?}: {}",
+            builder,
+            err
+        );
+    }
+}
@@ -1,16 +1,29 @@
 use crate::{
-    hittable::Hittable,
+    background::Background,
+    denoise::Denoiser,
+    framebuffer::Framebuffer,
+    hittable::{HitRecord, Hittable},
     interval::Interval,
     ray::Ray,
-    utils,
+    sampler::{SampleStream, Sampler},
     vec3::{self, Color, Point3, Vec3},
-    writer::PpmWriter,
-    INFINITY,
+    writer, INFINITY,
 };
 use anyhow::Result;
-use image::{ImageBuffer, Rgb, RgbImage};
 use indicatif::{ParallelProgressIterator, ProgressBar, ProgressStyle};
 use rayon::prelude::*;
+use std::sync::Arc;
+
+mod adaptive;
+mod aov;
+mod builder;
+
+pub use adaptive::AdaptiveSampling;
+pub use aov::Aov;
+pub use builder::*;
+
+use adaptive::PixelEstimate;
+use aov::AovSamples;
 
 #[derive(Debug)]
 pub struct Camera {
@@ -19,200 +32,267 @@ pub struct Camera {
     pixel00_loc: Point3,
     pixel_delta: [Vec3; 2],
     samples_per_pixel: i32,
-    pixel_samples_scale: f64,
     max_depth: i32,
     defocus_angle: f64,
     defocus_disk: [Vec3; 2],
+    forward: Vec3,
+    background: Arc<dyn Background>,
+    aovs: Vec<Aov>,
+    denoiser: Option<Denoiser>,
+    sampler: Arc<dyn Sampler>,
+    adaptive: Option<AdaptiveSampling>,
 }
 
 impl Camera {
-    const EMPTY_SPACES: &'static str = "          ";
-
-    pub fn new(
-        aspect_ratio: f64,
-        image_width: i32,
-        samples_per_pixel: i32,
-        max_depth: i32,
-        vfov: f64,
-        look_from: &Point3,
-        look_at: &Point3,
-        vup: &Vec3,
-        defocus_angle: f64,
-        focus_distance: f64,
-    ) -> Self {
-        let image_height = {
-            let image_height = (image_width as f64 / aspect_ratio) as i32;
-
-            if image_height < 1 {
-                1
-            } else {
-                image_height
-            }
-        };
-
-        let pixel_samples_scale = 1_f64 / samples_per_pixel as f64;
-
-        let center = *look_from;
-
-        let theta = utils::degrees_to_radians(vfov);
-        let h = (theta / 2_f64).tan();
-        let viewport_height = 2_f64 * h * focus_distance;
-        let viewport_width = viewport_height * (image_width as f64 / image_height as f64);
+    pub fn image_size(&self) -> [i32; 2] {
+        self.image_size
+    }
 
-        let w = vec3::unit_vector(&(*look_from - look_at));
-        let u = vec3::unit_vector(&vec3::cross(vup, &w));
-        let v = vec3::cross(&w, &u);
+    pub fn set_background(&mut self, background: Arc<dyn Background>) {
+        self.background = background
+    }
 
-        let viewport_u = viewport_width * u;
-        let viewport_v = viewport_height * -v;
+    /// Renders `world` into a framebuffer of linear colors, leaving the
+    /// encoding to the caller. Requested AOVs are attached as layers, and the
+    /// color is denoised if a denoiser is set.
+    ///
+    /// With adaptive sampling the number of samples of each pixel is kept in
+    /// the framebuffer's sample counts.
+    pub fn render(&self, world: &dyn Hittable) -> Framebuffer {
+        let [width, height] = self.image_size;
+        let passes = match &self.adaptive {
+            Some(adaptive) => adaptive.passes(self.samples_per_pixel as u32),
+            None => vec![self.samples_per_pixel as u32],
+        };
+        let bar = ProgressBar::new((width * height) as u64 * passes.len() as u64).with_style(
+            ProgressStyle::default_bar()
+                .template(
+                    "Rendering: [{eta_precise}] {bar:40.cyan/blue} {pos:>7}/{len:} pixel passes",
+                )
+                .unwrap_or_else(|_| ProgressStyle::default_bar()),
+        );
+        let mut pixels = vec![PixelEstimate::new(); (width * height) as usize];
+
+        for pass_samples in passes {
+            pixels
+                .par_iter_mut()
+                .enumerate()
+                .progress_with(bar.clone())
+                .filter(|(_, estimate)| !estimate.converged())
+                .for_each(|(ij, estimate)| {
+                    let j = ij as i32 / width;
+                    let i = ij as i32 % width;
+                    let first = estimate.samples();
+
+                    // Samples are summed in order so the float rounding does
+                    // not depend on how rayon splits the work
+                    for s in first..first + pass_samples {
+                        let mut samples = SampleStream::new(self.sampler.as_ref(), ij as u64, s);
+                        let (color, aov_samples) = self.sample(i, j, world, &mut samples);
+
+                        estimate.add(color, aov_samples);
+                    }
+                });
+
+            if let Some(adaptive) = &self.adaptive {
+                adaptive.update(&mut pixels, width as usize);
+            }
+            if pixels.iter().all(|estimate| estimate.converged()) {
+                break;
+            }
+        }
+        bar.finish();
 
-        let pixel_delta = [
-            viewport_u / image_width as f64,
-            viewport_v / image_height as f64,
-        ];
+        let (pixels, aov_samples): (Vec<(Color, u32)>, Vec<AovSamples>) = pixels
+            .iter()
+            .map(|estimate| {
+                let (color, samples, aov_samples) = estimate.finish();
 
-        let viewport_upper_left = center - (focus_distance * w) - (viewport_u + viewport_v) / 2_f64;
-        let pixel00_loc = viewport_upper_left + 0.5 * (pixel_delta.iter().sum::<Point3>());
+                ((color, samples), aov_samples)
+            })
+            .unzip();
+        let mut framebuffer = Framebuffer::from_pixels(width as usize, height as usize, pixels);
 
-        let defocus_radius =
-            focus_distance * utils::degrees_to_radians(defocus_angle / 2_f64).tan();
-        let defocus_disk = [u * defocus_radius, v * defocus_radius];
+        let recorded_aovs = self.recorded_aovs();
 
-        Self {
-            image_size: [image_width, image_height],
-            center,
-            pixel_delta,
-            pixel00_loc,
-            samples_per_pixel,
-            pixel_samples_scale,
-            max_depth,
-            defocus_angle,
-            defocus_disk,
+        if !recorded_aovs.is_empty() {
+            AovSamples::layers(&mut framebuffer, &recorded_aovs, &aov_samples);
         }
-    }
+        if let Some(denoiser) = &self.denoiser {
+            framebuffer = denoiser.denoise(&framebuffer);
 
-    pub fn render_ppm(&self, world: &dyn Hittable, output_file: &str) -> Result<()> {
-        let mut writer = PpmWriter::new(output_file);
-        let bar = ProgressBar::new((self.image_size[0] * self.image_size[1]) as u64).with_style(
-            ProgressStyle::default_bar().template(
-                "Rendering: [{eta_precise}] {bar:40.cyan/blue} {pos:>7}/{len:} scanlines",
-            )?,
-        );
-        let buffer = (0..(self.image_size[0] * self.image_size[1]))
-            .into_par_iter()
-            .progress_with(bar)
-            .map(|ij| {
-                let j = ij / self.image_size[0];
-                let i = ij % self.image_size[0];
-                let pixel_color = (0..self.samples_per_pixel)
-                    .into_par_iter()
-                    .map(|_| {
-                        let r = self.get_ray(i, j);
-
-                        Self::ray_color(&r, self.max_depth, world)
-                    })
-                    .sum::<Color>();
-
-                vec3::write_color(&(self.pixel_samples_scale * pixel_color))
-            })
-            .collect::<Vec<[i32; 3]>>();
-
-        writer.set_buffer(&buffer);
-        writer.write([self.image_size[0] as usize, self.image_size[1] as usize])?;
-
-        eprintln!("\r\n\nDone!{}", Self::EMPTY_SPACES.repeat(10));
+            for aov in Denoiser::FEATURES {
+                if !self.aovs.contains(&aov) {
+                    framebuffer.remove_layer(aov.name());
+                }
+            }
+        }
 
-        Ok(())
+        framebuffer
     }
 
-    pub fn render_png(&self, world: &dyn Hittable, output_file: &str) -> Result<()> {
-        let image_size = [self.image_size[0] as u32, self.image_size[1] as u32];
-        let mut buffer: RgbImage = ImageBuffer::new(image_size[0], image_size[1]);
-        let bar = ProgressBar::new((self.image_size[0] * self.image_size[1]) as u64).with_style(
-            ProgressStyle::default_bar().template(
-                "Rendering: [{eta_precise}] {bar:40.cyan/blue} {pos:>7}/{len:} scanlines",
-            )?,
-        );
-        let pixels = (0..(self.image_size[0] * self.image_size[1]))
-            .into_par_iter()
-            .progress_with(bar)
-            .map(|ij| {
-                let j = ij / self.image_size[0];
-                let i = ij % self.image_size[0];
-                let pixel_color = (0..self.samples_per_pixel)
-                    .into_par_iter()
-                    .map(|_| {
-                        let r = self.get_ray(i, j);
-
-                        Self::ray_color(&r, self.max_depth, world)
-                    })
-                    .sum::<Color>();
-
-                vec3::write_color(&(self.pixel_samples_scale * pixel_color))
-            })
-            .collect::<Vec<[i32; 3]>>();
-
-        buffer.enumerate_pixels_mut().for_each(|(x, y, pixel)| {
-            let rgb = pixels[(y * image_size[0] + x) as usize];
-
-            *pixel = Rgb([rgb[0] as u8, rgb[1] as u8, rgb[2] as u8]);
-        });
+    /// Renders `world` and writes it with the encoder matching the extension
+    /// of `output_file`.
+    pub fn render_to_file(&self, world: &dyn Hittable, output_file: &str) -> Result<()> {
+        writer::write_image(&self.render(world), output_file)
+    }
 
-        match buffer.save(output_file) {
-            Ok(()) => {
-                eprintln!("Done!");
+    /// The requested AOVs plus the features the denoiser needs.
+    fn recorded_aovs(&self) -> Vec<Aov> {
+        let mut aovs = self.aovs.clone();
 
-                Ok(())
-            }
-            Err(e) => {
-                eprintln!("Failed to write file: {}", e);
-                Err(e.into())
-            }
+        if self.denoiser.is_some() {
+            aovs.extend(Denoiser::FEATURES);
+            aovs.sort();
+            aovs.dedup();
         }
+
+        aovs
     }
 
-    fn get_ray(&self, i: i32, j: i32) -> Ray {
-        let offset = Self::sample_square();
+    /// Draws the pixel offset, lens position and time from the first five
+    /// sample dimensions; the lens sample is drawn even without defocus blur
+    /// so the dimensions of later decisions stay fixed.
+    fn get_ray(&self, i: i32, j: i32, samples: &mut SampleStream) -> Ray {
+        let offset = Self::sample_square(samples.next_2d());
+        let lens = samples.next_2d();
         let pixel_sample = self.pixel00_loc
             + ((i as f64 + offset.x()) * self.pixel_delta[0])
             + ((j as f64 + offset.y()) * self.pixel_delta[1]);
         let ray_origin = if self.defocus_angle <= 0_f64 {
             self.center
         } else {
-            self.defocus_disk_sample()
+            self.defocus_disk_sample(lens)
         };
         let ray_direction = pixel_sample - ray_origin;
-        let ray_time = utils::random();
+        let ray_time = samples.next_1d();
 
         Ray::new(ray_origin, ray_direction, ray_time)
     }
 
-    fn defocus_disk_sample(&self) -> Point3 {
-        let p = vec3::random_in_unit_disk();
+    fn defocus_disk_sample(&self, u: [f64; 2]) -> Point3 {
+        let p = vec3::sample_unit_disk(u);
 
         self.center + (p[0] * self.defocus_disk[0]) + (p[1] * self.defocus_disk[1])
     }
 
-    fn sample_square() -> Vec3 {
-        Vec3::new(utils::random() - 0.5, utils::random() - 0.5, 0_f64)
+    fn sample_square(u: [f64; 2]) -> Vec3 {
+        Vec3::new(u[0] - 0.5, u[1] - 0.5, 0_f64)
     }
 
-    fn ray_color(r: &Ray, depth: i32, world: &dyn Hittable) -> Color {
+    /// Traces one camera ray through pixel (`i`, `j`), keeping its first hit
+    /// for the AOVs.
+    fn sample(
+        &self,
+        i: i32,
+        j: i32,
+        world: &dyn Hittable,
+        samples: &mut SampleStream,
+    ) -> (Color, AovSamples) {
+        let r = self.get_ray(i, j, samples);
+
+        match world.hit(&r, &Interval::new(0.001, INFINITY), samples) {
+            Some(rec) => {
+                let aov_samples = if self.aovs.is_empty() && self.denoiser.is_none() {
+                    AovSamples::miss()
+                } else {
+                    AovSamples::hit(&r, &rec, &self.forward)
+                };
+
+                (
+                    self.shade(&r, &rec, self.max_depth, world, samples),
+                    aov_samples,
+                )
+            }
+            None => (self.background.value(&r), AovSamples::miss()),
+        }
+    }
+
+    fn ray_color(
+        &self,
+        r: &Ray,
+        depth: i32,
+        world: &dyn Hittable,
+        samples: &mut SampleStream,
+    ) -> Color {
         if depth <= 0 {
             return Color::zeroes();
         }
 
-        if let Some(rec) = world.hit(r, &Interval::new(0.001, INFINITY)) {
-            if let Some((attenuation, scattered)) = rec.mat.scatter(r, &rec) {
-                return attenuation * Self::ray_color(&scattered, depth - 1, world);
-            }
-
-            return Color::zeroes();
+        if let Some(rec) = world.hit(r, &Interval::new(0.001, INFINITY), samples) {
+            return self.shade(r, &rec, depth, world, samples);
         }
 
-        let unit_direction = vec3::unit_vector(r.direction());
-        let a = 0.5 * (unit_direction.y() + 1_f64);
+        self.background.value(r)
+    }
+
+    fn shade(
+        &self,
+        r: &Ray,
+        rec: &HitRecord,
+        depth: i32,
+        world: &dyn Hittable,
+        samples: &mut SampleStream,
+    ) -> Color {
+        let color_from_emission = rec.mat.emitted(rec.u, rec.v, &rec.p);
+
+        if let Some((attenuation, scattered)) = rec.mat.scatter(r, rec, samples) {
+            let color_from_scatter =
+                attenuation * self.ray_color(&scattered, depth - 1, world, samples);
+
+            return color_from_emission + color_from_scatter;
+        }
 
-        (1_f64 - a) * Color::new(1_f64, 1_f64, 1_f64) + a * Color::new(0.5, 0.7, 1_f64)
+        color_from_emission
     }
 }
+
+#[test]
+fn test_render_is_reproducible_across_thread_counts() {
+    use crate::hittable::{self, ConstantMedium, Dielectric, Lambertian, Metal, Sphere};
+
+    let mut world = hittable::test_world();
+
+    world.add(Arc::new(Sphere::new(
+        Point3::new(-2_f64, 0_f64, -3_f64),
+        0.8,
+        Arc::new(Dielectric::new(1.5)),
+    )));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(2_f64, 0_f64, -3_f64),
+        0.8,
+        Arc::new(Metal::new(Color::new(0.8, 0.6, 0.2), 0.3)),
+    )));
+    world.add(Arc::new(ConstantMedium::new(
+        Arc::new(Sphere::new(
+            Point3::new(0_f64, 0_f64, -5_f64),
+            1_f64,
+            Arc::new(Lambertian::new(Color::zeroes())),
+        )),
+        0.5,
+        Color::new(0.9, 0.9, 0.9),
+    )));
+
+    let camera = CameraBuilder::new()
+        .image_width(24)
+        .samples_per_pixel(6)
+        .defocus_angle(2_f64)
+        .focus_distance(3_f64);
+    let render = |seed: u64, threads: usize| {
+        let camera = camera.clone().seed(seed).build().expect("camera is valid");
+
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .expect("thread pool is built")
+            .install(|| camera.render(&world))
+            .pixels()
+            .to_vec()
+    };
+
+    let single = render(11, 1);
+
+    assert_eq!(single, render(11, 4));
+    assert_eq!(single, render(11, 4));
+    assert_ne!(single, render(12, 4));
+}
@@ -0,0 +1,370 @@
+use crate::{
+    camera::Aov,
+    framebuffer::{Framebuffer, Layer},
+    vec3::Color,
+    INFINITY,
+};
+use rayon::prelude::*;
+use serde::Deserialize;
+
+/// Edge-avoiding à-trous wavelet filter (Dammertz et al., 2010) that smooths
+/// the noise of a render while keeping the edges visible in its `normal`,
+/// `albedo` and `depth` layers.
+///
+/// Every pass blurs with a 5x5 B3-spline kernel whose taps are `2^pass`
+/// pixels apart, weighting each tap by how close its color and features are
+/// to those of the center pixel. The color is divided by the albedo before
+/// filtering and multiplied back afterwards, which keeps textures sharp.
+/// Feature layers missing from the framebuffer are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Denoiser {
+    /// Number of passes. Passes whose taps would all fall outside the image
+    /// are skipped.
+    pub iterations: u32,
+    /// Color distance over which a tap loses most of its weight; halved
+    /// after every pass so later, wider passes only average similar colors.
+    pub sigma_color: f64,
+    pub sigma_normal: f64,
+    pub sigma_albedo: f64,
+    /// Depth difference relative to the depth of the center pixel.
+    pub sigma_depth: f64,
+}
+
+/// Feature layers of the framebuffer being filtered.
+struct Features<'a> {
+    normal: Option<&'a Layer>,
+    albedo: Option<&'a Layer>,
+    depth: Option<&'a Layer>,
+}
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Self {
+            iterations: 3,
+            sigma_color: 1_f64,
+            sigma_normal: 0.2,
+            sigma_albedo: 0.1,
+            sigma_depth: 0.1,
+        }
+    }
+}
+
+impl Denoiser {
+    /// AOVs the filter uses as edge-stopping features.
+    pub const FEATURES: [Aov; 3] = [Aov::Depth, Aov::Normal, Aov::Albedo];
+
+    const KERNEL: [f64; 5] = [0.0625, 0.25, 0.375, 0.25, 0.0625];
+    /// Albedo below which a channel is filtered without demodulation.
+    const MIN_ALBEDO: f64 = 1e-3;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    pub fn sigma_color(mut self, sigma_color: f64) -> Self {
+        self.sigma_color = sigma_color;
+        self
+    }
+
+    pub fn sigma_normal(mut self, sigma_normal: f64) -> Self {
+        self.sigma_normal = sigma_normal;
+        self
+    }
+
+    pub fn sigma_albedo(mut self, sigma_albedo: f64) -> Self {
+        self.sigma_albedo = sigma_albedo;
+        self
+    }
+
+    pub fn sigma_depth(mut self, sigma_depth: f64) -> Self {
+        self.sigma_depth = sigma_depth;
+        self
+    }
+
+    /// A copy of `framebuffer` with its color filtered. Sample counts and
+    /// layers are kept unchanged.
+    pub fn denoise(&self, framebuffer: &Framebuffer) -> Framebuffer {
+        let width = framebuffer.width();
+        // ceil(log2) of the larger side; wider passes only see the center tap
+        let passes = usize::BITS - (width.max(framebuffer.height()).max(1) - 1).leading_zeros();
+        let features = Features {
+            normal: framebuffer.layer(Aov::Normal.name()),
+            albedo: framebuffer.layer(Aov::Albedo.name()),
+            depth: framebuffer.layer(Aov::Depth.name()),
+        };
+        let mut colors = framebuffer
+            .pixels()
+            .iter()
+            .enumerate()
+            .map(|(index, color)| Self::demodulate(color, &features.albedo(index)))
+            .collect::<Vec<Color>>();
+
+        for pass in 0..self.iterations.min(passes) {
+            let step = 1_isize << pass;
+            let sigma_color = self.sigma_color / 2_f64.powi(pass as i32);
+
+            colors = (0..colors.len())
+                .into_par_iter()
+                .map(|index| self.filter(&colors, &features, width, index, step, sigma_color))
+                .collect();
+        }
+
+        let mut denoised = framebuffer.clone();
+
+        for (index, color) in colors.iter().enumerate() {
+            let (x, y) = (index % width, index / width);
+
+            denoised.set_pixel(
+                x,
+                y,
+                Self::remodulate(color, &features.albedo(index)),
+                framebuffer.samples(x, y),
+            );
+        }
+
+        denoised
+    }
+
+    /// One pass of the filter at pixel `index`, with taps `step` pixels
+    /// apart.
+    fn filter(
+        &self,
+        colors: &[Color],
+        features: &Features,
+        width: usize,
+        index: usize,
+        step: isize,
+        sigma_color: f64,
+    ) -> Color {
+        let height = (colors.len() / width) as isize;
+        let (x, y) = ((index % width) as isize, (index / width) as isize);
+        let center = colors[index];
+        let mut sum = Color::zeroes();
+        let mut total_weight = 0_f64;
+
+        for (dy, ky) in Self::KERNEL.iter().enumerate() {
+            let qy = y + (dy as isize - 2) * step;
+
+            if qy < 0 || qy >= height {
+                continue;
+            }
+
+            for (dx, kx) in Self::KERNEL.iter().enumerate() {
+                let qx = x + (dx as isize - 2) * step;
+
+                if qx < 0 || qx >= width as isize {
+                    continue;
+                }
+
+                let q = qy as usize * width + qx as usize;
+                let weight = kx
+                    * ky
+                    * Self::similarity((center - colors[q]).length_squared(), sigma_color)
+                    * self.feature_similarity(features, index, q);
+
+                sum += weight * colors[q];
+                total_weight += weight;
+            }
+        }
+
+        // The center tap always has a positive weight
+        sum / total_weight
+    }
+
+    fn feature_similarity(&self, features: &Features, p: usize, q: usize) -> f64 {
+        let mut similarity = 1_f64;
+
+        if let Some(normal) = features.normal {
+            similarity *= Self::similarity(
+                Self::distance_squared(normal.pixel(p), normal.pixel(q)),
+                self.sigma_normal,
+            );
+        }
+        if let Some(albedo) = features.albedo {
+            similarity *= Self::similarity(
+                Self::distance_squared(albedo.pixel(p), albedo.pixel(q)),
+                self.sigma_albedo,
+            );
+        }
+        if let Some(depth) = features.depth {
+            let (zp, zq) = (depth.pixel(p)[0], depth.pixel(q)[0]);
+            // Misses have infinite depth and only match each other
+            let distance = match (zp.is_finite(), zq.is_finite()) {
+                (true, true) => (zp - zq) / zp.abs().max(f64::EPSILON),
+                (false, false) => 0_f64,
+                _ => INFINITY,
+            };
+
+            similarity *= Self::similarity(distance * distance, self.sigma_depth);
+        }
+
+        similarity
+    }
+
+    /// Gaussian falloff `exp(-d² / σ²)` of the squared distance `d²`.
+    fn similarity(distance_squared: f64, sigma: f64) -> f64 {
+        if distance_squared == 0_f64 {
+            return 1_f64;
+        }
+
+        (-distance_squared / (sigma * sigma)).exp()
+    }
+
+    fn distance_squared(a: &[f64], b: &[f64]) -> f64 {
+        a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum()
+    }
+
+    fn demodulate(color: &Color, albedo: &Color) -> Color {
+        Self::per_channel(color, albedo, |c, a| c / a)
+    }
+
+    fn remodulate(color: &Color, albedo: &Color) -> Color {
+        Self::per_channel(color, albedo, |c, a| c * a)
+    }
+
+    fn per_channel(color: &Color, albedo: &Color, f: impl Fn(f64, f64) -> f64) -> Color {
+        let channel = |c: usize| {
+            if albedo[c] > Self::MIN_ALBEDO {
+                f(color[c], albedo[c])
+            } else {
+                color[c]
+            }
+        };
+
+        Color::new(channel(0), channel(1), channel(2))
+    }
+}
+
+impl Features<'_> {
+    /// Albedo of pixel `index`, or white without an albedo layer.
+    fn albedo(&self, index: usize) -> Color {
+        match self.albedo {
+            Some(albedo) => {
+                let albedo = albedo.pixel(index);
+
+                Color::new(albedo[0], albedo[1], albedo[2])
+            }
+            None => Color::new(1_f64, 1_f64, 1_f64),
+        }
+    }
+}
+
+#[test]
+fn test_denoiser_keeps_feature_edges() {
+    // Left half faces the camera, right half faces up; both are noisy gray
+    let (width, height) = (8, 8);
+    let pixels = (0..width * height)
+        .map(|index| {
+            let base = if index % width < width / 2 { 0.2 } else { 0.8 };
+            let noise = if (index * 7) % 3 == 0 { 0.1 } else { -0.05 };
+            let v = base + noise;
+
+            (Color::new(v, v, v), 4)
+        })
+        .collect();
+    let normals = (0..width * height)
+        .flat_map(|index| {
+            if index % width < width / 2 {
+                [0_f64, 0_f64, 1_f64]
+            } else {
+                [0_f64, 1_f64, 0_f64]
+            }
+        })
+        .collect();
+    let mut framebuffer = Framebuffer::from_pixels(width, height, pixels);
+
+    framebuffer.add_layer(
+        Aov::Normal.name(),
+        Layer::new(Aov::Normal.channels(), normals),
+    );
+
+    let denoised = Denoiser::new().denoise(&framebuffer);
+
+    for y in 0..height {
+        for x in 0..width {
+            let expected = if x < width / 2 { 0.2 } else { 0.8 };
+
+            assert!(
+                (denoised.pixel(x, y).x() - expected).abs() < 0.06,
+                "({}, {}): {:?}",
+                x,
+                y,
+                denoised.pixel(x, y)
+            );
+            assert_eq!(framebuffer.samples(x, y), denoised.samples(x, y));
+        }
+    }
+    assert!(denoised.layer(Aov::Normal.name()).is_some());
+}
+
+#[test]
+fn test_denoiser_skips_passes_wider_than_the_image() {
+    let (width, height) = (5, 3);
+    let pixels = (0..width * height)
+        .map(|index| {
+            let v = (index % 4) as f64 * 0.25;
+
+            (Color::new(v, v, v), 1)
+        })
+        .collect();
+    let framebuffer = Framebuffer::from_pixels(width, height, pixels);
+
+    // The taps of pass 62 and later are more than `isize::MAX` pixels apart
+    let denoised = Denoiser::new().iterations(u32::MAX).denoise(&framebuffer);
+
+    assert_eq!(
+        Denoiser::new().iterations(3).denoise(&framebuffer).pixels(),
+        denoised.pixels()
+    );
+}
+
+#[test]
+fn test_denoiser_reduces_render_error() {
+    use crate::{camera::CameraBuilder, hittable};
+
+    let world = hittable::test_world();
+
+    let camera = CameraBuilder::new()
+        .image_width(48)
+        .max_depth(8)
+        .aovs(&Denoiser::FEATURES);
+    let mean_squared_error = |a: &Framebuffer, b: &Framebuffer| {
+        a.pixels()
+            .iter()
+            .zip(b.pixels())
+            .map(|(a, b)| (*a - *b).length_squared())
+            .sum::<f64>()
+            / a.pixels().len() as f64
+    };
+
+    let reference = camera
+        .clone()
+        .samples_per_pixel(256)
+        .seed(1)
+        .build()
+        .expect("camera is valid")
+        .render(&world);
+    let noisy = camera
+        .samples_per_pixel(8)
+        .seed(7)
+        .build()
+        .expect("camera is valid")
+        .render(&world);
+    let denoised = Denoiser::new().denoise(&noisy);
+
+    let noisy_error = mean_squared_error(&noisy, &reference);
+    let denoised_error = mean_squared_error(&denoised, &reference);
+
+    assert!(
+        denoised_error < 0.5 * noisy_error,
+        "denoised {} vs noisy {}",
+        denoised_error,
+        noisy_error
+    );
+}
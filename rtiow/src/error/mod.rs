@@ -4,12 +4,26 @@ use thiserror::Error;
 #[derive(Debug, Clone, Error)]
 pub enum RTError {
     EmptyBufferOnWrite(String),
+    NonInvertibleTransform,
+    InvalidMesh(String),
+    ObjParse(usize, String),
+    MtlParse(usize, String),
+    InvalidScene(String),
+    InvalidCamera(String),
+    UnsupportedFormat(String),
 }
 
 impl Display for RTError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             Self::EmptyBufferOnWrite(path) => write!(f, "EmptyBufferOnWrite: {:?}", path),
+            Self::NonInvertibleTransform => write!(f, "NonInvertibleTransform"),
+            Self::InvalidMesh(reason) => write!(f, "InvalidMesh: {}", reason),
+            Self::ObjParse(line, reason) => write!(f, "ObjParse: line {}: {}", line, reason),
+            Self::MtlParse(line, reason) => write!(f, "MtlParse: line {}: {}", line, reason),
+            Self::InvalidScene(reason) => write!(f, "InvalidScene: {}", reason),
+            Self::InvalidCamera(reason) => write!(f, "InvalidCamera: {}", reason),
+            Self::UnsupportedFormat(path) => write!(f, "UnsupportedFormat: {:?}", path),
         }
     }
 }
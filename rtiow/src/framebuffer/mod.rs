@@ -0,0 +1,284 @@
+use crate::vec3::{self, Color};
+use std::collections::BTreeMap;
+
+/// The result of a render before it is encoded: one linear, unclamped color
+/// per pixel together with the number of samples averaged into it. Pixels are
+/// stored row by row starting from the top-left corner.
+///
+/// Renders may attach additional named layers, such as depth or normals,
+/// which writers that support them store next to the color.
+#[derive(Debug, Clone)]
+pub struct Framebuffer {
+    size: [usize; 2],
+    pixels: Vec<Color>,
+    samples: Vec<u32>,
+    layers: BTreeMap<String, Layer>,
+}
+
+/// Per-pixel data of an additional framebuffer layer, with the samples of
+/// all channels interleaved in the same pixel order as the color.
+#[derive(Debug, Clone)]
+pub struct Layer {
+    channels: Vec<String>,
+    data: Vec<f64>,
+}
+
+impl Framebuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            size: [width, height],
+            pixels: vec![Color::zeroes(); width * height],
+            samples: vec![0; width * height],
+            layers: BTreeMap::new(),
+        }
+    }
+
+    /// `pixels` holds `(color, sample count)` pairs in row-major order.
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<(Color, u32)>) -> Self {
+        assert_eq!(
+            width * height,
+            pixels.len(),
+            "pixel count does not match the framebuffer size"
+        );
+
+        let (pixels, samples) = pixels.into_iter().unzip();
+
+        Self {
+            size: [width, height],
+            pixels,
+            samples,
+            layers: BTreeMap::new(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.size[0]
+    }
+
+    pub fn height(&self) -> usize {
+        self.size[1]
+    }
+
+    pub fn size(&self) -> [usize; 2] {
+        self.size
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[self.index(x, y)]
+    }
+
+    pub fn samples(&self, x: usize, y: usize) -> u32 {
+        self.samples[self.index(x, y)]
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Color, samples: u32) {
+        let index = self.index(x, y);
+
+        self.pixels[index] = color;
+        self.samples[index] = samples;
+    }
+
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    pub fn sample_counts(&self) -> &[u32] {
+        &self.samples
+    }
+
+    /// Attaches `layer` under `name`, replacing any layer of the same name.
+    pub fn add_layer(&mut self, name: &str, layer: Layer) {
+        assert_eq!(
+            self.pixels.len() * layer.channels.len(),
+            layer.data.len(),
+            "layer {:?} does not match the framebuffer size",
+            name
+        );
+
+        self.layers.insert(name.to_string(), layer);
+    }
+
+    pub fn layer(&self, name: &str) -> Option<&Layer> {
+        self.layers.get(name)
+    }
+
+    pub fn remove_layer(&mut self, name: &str) -> Option<Layer> {
+        self.layers.remove(name)
+    }
+
+    /// A framebuffer showing the layer `name` as its color, so it can be
+    /// written with any writer: one channel becomes gray, two channels red
+    /// and green, and the first three channels are used otherwise.
+    pub fn layer_image(&self, name: &str) -> Option<Self> {
+        let layer = self.layer(name)?;
+        let pixels = (0..self.pixels.len())
+            .map(|index| {
+                let color = match *layer.pixel(index) {
+                    [y] => Color::new(y, y, y),
+                    [r, g] => Color::new(r, g, 0_f64),
+                    [r, g, b, ..] => Color::new(r, g, b),
+                    [] => Color::zeroes(),
+                };
+
+                (color, self.samples[index])
+            })
+            .collect();
+
+        Some(Self::from_pixels(self.size[0], self.size[1], pixels))
+    }
+
+    /// A framebuffer coloring every pixel by its sample count relative to
+    /// the largest count, from dark blue for few samples over cyan and yellow
+    /// to red for the most.
+    pub fn sample_heatmap(&self) -> Self {
+        const RAMP: [[f64; 3]; 4] = [
+            [0.05, 0.05, 0.3],
+            [0.1, 0.65, 0.9],
+            [1_f64, 0.85, 0.1],
+            [0.8, 0.1, 0.1],
+        ];
+
+        let max_samples = self.samples.iter().copied().max().unwrap_or(0).max(1);
+        let pixels = self
+            .samples
+            .iter()
+            .map(|samples| {
+                let t = *samples as f64 / max_samples as f64 * (RAMP.len() - 1) as f64;
+                let stop = (t as usize).min(RAMP.len() - 2);
+                let f = t - stop as f64;
+                // The ramp is given in sRGB, which writers encode again
+                let channel = |c: usize| {
+                    vec3::srgb_to_linear((1_f64 - f) * RAMP[stop][c] + f * RAMP[stop + 1][c])
+                };
+
+                (Color::new(channel(0), channel(1), channel(2)), *samples)
+            })
+            .collect();
+
+        Self::from_pixels(self.size[0], self.size[1], pixels)
+    }
+
+    /// Additional layers in name order.
+    pub fn layers(&self) -> impl Iterator<Item = (&str, &Layer)> {
+        self.layers
+            .iter()
+            .map(|(name, layer)| (name.as_str(), layer))
+    }
+
+    fn index(&self, x: usize, y: usize) -> usize {
+        assert!(
+            x < self.size[0] && y < self.size[1],
+            "pixel ({}, {}) is outside a {}x{} framebuffer",
+            x,
+            y,
+            self.size[0],
+            self.size[1]
+        );
+
+        y * self.size[0] + x
+    }
+}
+
+impl Layer {
+    /// `data` holds `channels.len()` interleaved samples per pixel.
+    pub fn new(channels: &[&str], data: Vec<f64>) -> Self {
+        assert!(!channels.is_empty(), "a layer needs at least one channel");
+
+        Self {
+            channels: channels.iter().map(|channel| channel.to_string()).collect(),
+            data,
+        }
+    }
+
+    pub fn channels(&self) -> &[String] {
+        &self.channels
+    }
+
+    /// Samples of the pixel at row-major position `index`.
+    pub fn pixel(&self, index: usize) -> &[f64] {
+        let channels = self.channels.len();
+
+        &self.data[index * channels..(index + 1) * channels]
+    }
+
+    /// Every sample of the channel at `channel`, in pixel order.
+    pub fn channel(&self, channel: usize) -> impl Iterator<Item = f64> + '_ {
+        self.data
+            .iter()
+            .skip(channel)
+            .step_by(self.channels.len())
+            .copied()
+    }
+
+    pub fn data(&self) -> &[f64] {
+        &self.data
+    }
+}
+
+#[test]
+fn test_framebuffer_is_row_major() {
+    let pixels = (0..6)
+        .map(|i| (Color::new(i as f64, 0_f64, 0_f64), i as u32 + 1))
+        .collect::<Vec<_>>();
+    let mut framebuffer = Framebuffer::from_pixels(3, 2, pixels);
+
+    assert_eq!(5_f64, framebuffer.pixel(2, 1).x());
+    assert_eq!(2, framebuffer.samples(1, 0));
+
+    framebuffer.set_pixel(0, 1, Color::new(0_f64, 4_f64, 0_f64), 7);
+
+    assert_eq!(4_f64, framebuffer.pixels()[3].y());
+    assert_eq!(7, framebuffer.sample_counts()[3]);
+}
+
+#[test]
+fn test_sample_heatmap() {
+    let pixels = [1, 4, 16]
+        .iter()
+        .map(|samples| (Color::zeroes(), *samples))
+        .collect();
+    let heatmap = Framebuffer::from_pixels(3, 1, pixels).sample_heatmap();
+    let red = Color::new(
+        vec3::srgb_to_linear(0.8),
+        vec3::srgb_to_linear(0.1),
+        vec3::srgb_to_linear(0.1),
+    );
+
+    assert_eq!(red, heatmap.pixel(2, 0));
+    assert!(heatmap.pixel(0, 0).z() > heatmap.pixel(0, 0).x());
+    assert_eq!(&[1, 4, 16], heatmap.sample_counts());
+}
+
+#[test]
+fn test_framebuffer_layers() {
+    let mut framebuffer = Framebuffer::new(2, 1);
+
+    framebuffer.add_layer(
+        "normal",
+        Layer::new(
+            &["X", "Y", "Z"],
+            vec![0_f64, 1_f64, 0_f64, 1_f64, 0_f64, 0_f64],
+        ),
+    );
+    framebuffer.add_layer("depth", Layer::new(&["Z"], vec![2_f64, 3_f64]));
+
+    let names = framebuffer
+        .layers()
+        .map(|(name, _)| name)
+        .collect::<Vec<_>>();
+    let normal = framebuffer.layer("normal").expect("normal layer exists");
+
+    assert_eq!(vec!["depth", "normal"], names);
+    assert_eq!([1_f64, 0_f64, 0_f64], normal.pixel(1));
+    assert_eq!(vec![1_f64, 0_f64], normal.channel(1).collect::<Vec<_>>());
+
+    let depth = framebuffer
+        .layer_image("depth")
+        .expect("depth layer exists");
+
+    assert_eq!(Color::new(3_f64, 3_f64, 3_f64), depth.pixel(1, 0));
+    assert!(depth.layers().next().is_none());
+    assert!(framebuffer.layer_image("albedo").is_none());
+    assert!(framebuffer.remove_layer("depth").is_some());
+    assert!(framebuffer.layer("depth").is_none());
+}
@@ -1,4 +1,9 @@
-use crate::{interval::Interval, ray::Ray, vec3::Point3};
+use crate::{
+    interval::Interval,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+use std::ops::Add;
 
 #[derive(Debug, Clone)]
 pub struct Aabb {
@@ -36,7 +41,7 @@ impl Aabb {
             Interval::new(b[2], a[2])
         };
 
-        Self { x, y, z }
+        Self::pad_to_minimums(Self { x, y, z })
     }
 
     pub fn surrounding_box(box0: &Self, box1: &Self) -> Self {
@@ -58,25 +63,23 @@ impl Aabb {
     pub fn hit(&self, r: &Ray, ray_t: &Interval) -> bool {
         let ray_orig = r.origin();
         let ray_dir = r.direction();
+        let mut ray_t = *ray_t;
 
         for axis in 0..3 {
             let ax = self.axis_interval(axis);
             let adinv = 1_f64 / ray_dir[axis];
             let t0 = (ax.min - ray_orig[axis]) * adinv;
             let t1 = (ax.max - ray_orig[axis]) * adinv;
-            let (min, max) = if t0 < t1 {
-                let min = if t0 > ray_t.min { t0 } else { ray_t.min };
-                let max = if t1 < ray_t.max { t1 } else { ray_t.max };
+            let (t_near, t_far) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
 
-                (min, max)
-            } else {
-                let min = if t1 > ray_t.min { t1 } else { ray_t.min };
-                let max = if t0 < ray_t.max { t0 } else { ray_t.max };
-
-                (min, max)
-            };
+            if t_near > ray_t.min {
+                ray_t.min = t_near;
+            }
+            if t_far < ray_t.max {
+                ray_t.max = t_far;
+            }
 
-            if max <= min {
+            if ray_t.max <= ray_t.min {
                 return false;
             }
         }
@@ -84,6 +87,20 @@ impl Aabb {
         true
     }
 
+    pub fn surface_area(&self) -> f64 {
+        let [dx, dy, dz] = [self.x.size(), self.y.size(), self.z.size()];
+
+        2_f64 * (dx * dy + dy * dz + dz * dx)
+    }
+
+    pub fn centroid(&self) -> Point3 {
+        Point3::new(
+            0.5 * (self.x.min + self.x.max),
+            0.5 * (self.y.min + self.y.max),
+            0.5 * (self.z.min + self.z.max),
+        )
+    }
+
     pub fn longest_axis(&self) -> usize {
         if self.x.size() > self.y.size() {
             if self.x.size() > self.z.size() {
@@ -99,4 +116,54 @@ impl Aabb {
             }
         }
     }
+
+    fn pad_to_minimums(bbox: Self) -> Self {
+        let delta = 1e-4;
+        let pad = |ax: Interval| {
+            if ax.size() < delta {
+                ax.expand(delta)
+            } else {
+                ax
+            }
+        };
+
+        Self {
+            x: pad(bbox.x),
+            y: pad(bbox.y),
+            z: pad(bbox.z),
+        }
+    }
+}
+
+impl Add<Vec3> for Aabb {
+    type Output = Self;
+
+    fn add(self, offset: Vec3) -> Self::Output {
+        Self {
+            x: self.x + offset.x(),
+            y: self.y + offset.y(),
+            z: self.z + offset.z(),
+        }
+    }
+}
+
+#[test]
+fn test_aabb_hit_narrows_interval_across_axes() {
+    use crate::INFINITY;
+
+    let bbox = Aabb::new(Point3::zeroes(), Point3::new(1_f64, 1_f64, 1_f64));
+    // Overlaps each slab on its own, but never all three at once.
+    let r = Ray::new(
+        Point3::new(-1_f64, 3.5, 0.5),
+        Vec3::new(1_f64, -1_f64, 0_f64),
+        0_f64,
+    );
+    let through = Ray::new(
+        Point3::new(-1_f64, 0.5, 0.5),
+        Vec3::new(1_f64, 0_f64, 0_f64),
+        0_f64,
+    );
+
+    assert!(!bbox.hit(&r, &Interval::new(0.001, INFINITY)));
+    assert!(bbox.hit(&through, &Interval::new(0.001, INFINITY)));
 }
@@ -1,111 +1,457 @@
 use super::{Aabb, HitRecord, Hittable, HittableList};
-use crate::{interval::Interval, ray::Ray, vec3::Point3};
-use anyhow::Result;
-use rayon::prelude::*;
-use std::{cmp::Ordering, sync::Arc};
+use crate::{interval::Interval, ray::Ray, sampler::SampleStream, vec3::Point3, INFINITY};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitMethod {
+    /// Split at the median centroid along the longest axis of the centroids.
+    Median,
+    /// Binned surface area heuristic over the primitive centroids.
+    Sah,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BvhOptions {
+    pub split_method: SplitMethod,
+    pub max_leaf_size: usize,
+    pub bins: usize,
+}
 
 #[derive(Debug)]
 pub struct BvhNode {
-    left: Arc<dyn Hittable>,
-    right: Arc<dyn Hittable>,
+    children: BvhChildren,
     bbox: Aabb,
 }
 
-impl BvhNode {
-    pub fn new(objects: &mut Vec<Arc<dyn Hittable>>, start: usize, end: usize) -> Result<Self> {
-        let mut bbox = Aabb::EMPTY;
-
-        for object_index in start..end {
-            bbox = Aabb::surrounding_box(
-                &bbox,
-                &objects[object_index]
-                    .bounding_box()
-                    .expect("Bounding box not available"),
-            );
+#[derive(Debug)]
+enum BvhChildren {
+    Leaf(Vec<Arc<dyn Hittable>>),
+    Interior(Arc<dyn Hittable>, Arc<dyn Hittable>),
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct BvhPrimitive {
+    pub index: usize,
+    pub bbox: Aabb,
+    pub centroid: Point3,
+}
+
+impl Default for BvhOptions {
+    fn default() -> Self {
+        Self {
+            split_method: SplitMethod::Sah,
+            max_leaf_size: 4,
+            bins: 12,
         }
+    }
+}
 
-        let axis = bbox.longest_axis();
-        let comparator = match axis {
-            0 => Self::box_x_compare,
-            1 => Self::box_y_compare,
-            _ => Self::box_z_compare,
-        };
-        let object_span = end - start;
+impl BvhPrimitive {
+    pub fn from_objects(objects: &[Arc<dyn Hittable>]) -> Vec<Self> {
+        objects
+            .iter()
+            .enumerate()
+            .map(|(index, object)| {
+                let bbox = object.bounding_box().expect("Bounding box not available");
+                let centroid = bbox.centroid();
 
-        let (left, right) = match object_span {
-            1 => (objects[start].clone(), objects[start].clone()),
-            2 => (objects[start].clone(), objects[start + 1].clone()),
-            _ => {
-                objects[start..end].par_sort_by(|a, b| comparator(a, b));
+                Self {
+                    index,
+                    bbox,
+                    centroid,
+                }
+            })
+            .collect()
+    }
+}
 
-                let mid = start + object_span / 2;
-                let left: Arc<dyn Hittable> = Arc::new(Self::new(objects, start, mid)?);
-                let right: Arc<dyn Hittable> = Arc::new(Self::new(objects, mid, end)?);
+impl BvhNode {
+    /// Builds a tree that splits at the median centroid.
+    pub fn from(list: &HittableList) -> Self {
+        Self::build(
+            list,
+            BvhOptions {
+                split_method: SplitMethod::Median,
+                ..BvhOptions::default()
+            },
+        )
+    }
 
-                (left, right)
-            }
-        };
+    /// Builds the same tree as `LinearBvh::new` with the same options.
+    pub fn build(list: &HittableList, options: BvhOptions) -> Self {
+        let mut primitives = BvhPrimitive::from_objects(&list.objects);
 
-        Ok(Self { left, right, bbox })
+        Self::build_node(&list.objects, &mut primitives, &options)
     }
 
-    pub fn from(list: &HittableList) -> Result<Self> {
-        let mut objects = list.objects.clone();
-        let length = objects.len();
+    fn build_node(
+        objects: &[Arc<dyn Hittable>],
+        primitives: &mut [BvhPrimitive],
+        options: &BvhOptions,
+    ) -> Self {
+        const PARALLEL_THRESHOLD: usize = 4096;
+
+        let bbox = primitives.iter().fold(Aabb::EMPTY, |bbox, primitive| {
+            Aabb::surrounding_box(&bbox, &primitive.bbox)
+        });
 
-        Self::new(&mut objects, 0, length)
+        match partition(primitives, options) {
+            None => Self {
+                children: BvhChildren::Leaf(
+                    primitives
+                        .iter()
+                        .map(|primitive| objects[primitive.index].clone())
+                        .collect(),
+                ),
+                bbox,
+            },
+            Some((mid, _)) => {
+                let (left, right) = primitives.split_at_mut(mid);
+                let (left, right) = if left.len() + right.len() > PARALLEL_THRESHOLD {
+                    rayon::join(
+                        || Self::build_node(objects, left, options),
+                        || Self::build_node(objects, right, options),
+                    )
+                } else {
+                    (
+                        Self::build_node(objects, left, options),
+                        Self::build_node(objects, right, options),
+                    )
+                };
+
+                Self {
+                    children: BvhChildren::Interior(Arc::new(left), Arc::new(right)),
+                    bbox,
+                }
+            }
+        }
     }
+}
 
-    fn box_compare(a: &Arc<dyn Hittable>, b: &Arc<dyn Hittable>, axis_index: usize) -> Ordering {
-        let a_axis_interval = a
-            .bounding_box()
-            .unwrap_or(Aabb::new(Point3::zeroes(), Point3::zeroes()));
-        let b_axis_interval = b
-            .bounding_box()
-            .unwrap_or(Aabb::new(Point3::zeroes(), Point3::zeroes()));
+impl Hittable for BvhNode {
+    fn hit(&self, r: &Ray, ray_t: &Interval, samples: &mut SampleStream) -> Option<HitRecord<'_>> {
+        if !self.bbox.hit(r, ray_t) {
+            return None;
+        }
+
+        match &self.children {
+            BvhChildren::Leaf(objects) => {
+                let mut hit_anything = None;
+                let mut closest_so_far = ray_t.max;
 
-        a_axis_interval
-            .axis_interval(axis_index)
-            .min
-            .partial_cmp(&b_axis_interval.axis_interval(axis_index).min)
-            .expect("No bounding box in Bvh::new")
+                for object in objects.iter() {
+                    if let Some(rec) =
+                        object.hit(r, &Interval::new(ray_t.min, closest_so_far), samples)
+                    {
+                        closest_so_far = rec.t;
+                        hit_anything = Some(rec);
+                    }
+                }
+
+                hit_anything
+            }
+            BvhChildren::Interior(left, right) => {
+                let hit_left = left.hit(r, ray_t, samples);
+                let t = hit_left.as_ref().map(|rec| rec.t).unwrap_or(ray_t.max);
+                let hit_right = right.hit(r, &Interval::new(ray_t.min, t), samples);
+
+                // Anything found on the right is closer, since it was searched up to `t`.
+                hit_right.or(hit_left)
+            }
+        }
     }
 
-    fn box_x_compare(a: &Arc<dyn Hittable>, b: &Arc<dyn Hittable>) -> Ordering {
-        Self::box_compare(a, b, 0)
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bbox.clone())
     }
+}
+
+/// Reorders `primitives` so that the two halves of the chosen split are
+/// contiguous and returns the split index and axis, or `None` when the
+/// primitives should be kept together in one leaf.
+pub(crate) fn partition(
+    primitives: &mut [BvhPrimitive],
+    options: &BvhOptions,
+) -> Option<(usize, usize)> {
+    let n = primitives.len();
 
-    fn box_y_compare(a: &Arc<dyn Hittable>, b: &Arc<dyn Hittable>) -> Ordering {
-        Self::box_compare(a, b, 1)
+    if n <= 1 {
+        return None;
     }
 
-    fn box_z_compare(a: &Arc<dyn Hittable>, b: &Arc<dyn Hittable>) -> Ordering {
-        Self::box_compare(a, b, 2)
+    let mut centroid_min = [INFINITY; 3];
+    let mut centroid_max = [-INFINITY; 3];
+
+    for primitive in primitives.iter() {
+        for axis in 0..3 {
+            centroid_min[axis] = centroid_min[axis].min(primitive.centroid[axis]);
+            centroid_max[axis] = centroid_max[axis].max(primitive.centroid[axis]);
+        }
     }
-}
 
-impl Hittable for BvhNode {
-    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
-        if !self.bbox.hit(r, ray_t) {
+    let axis = (0..3)
+        .max_by(|&a, &b| {
+            (centroid_max[a] - centroid_min[a]).total_cmp(&(centroid_max[b] - centroid_min[b]))
+        })
+        .unwrap_or(0);
+    let extent = centroid_max[axis] - centroid_min[axis];
+
+    if options.split_method == SplitMethod::Median {
+        if n <= options.max_leaf_size {
             return None;
         }
 
-        let hit_left = if let Some(rec) = self.left.hit(r, ray_t) {
-            Some(rec)
-        } else {
+        primitives
+            .select_nth_unstable_by(n / 2, |a, b| a.centroid[axis].total_cmp(&b.centroid[axis]));
+
+        return Some((n / 2, axis));
+    }
+    if extent <= 0_f64 {
+        // Every centroid coincides, so no plane separates them.
+        return if n <= options.max_leaf_size {
             None
+        } else {
+            Some((n / 2, axis))
+        };
+    }
+
+    let bins = options.bins.max(2);
+    let bin_of = |primitive: &BvhPrimitive| {
+        let b = (bins as f64 * (primitive.centroid[axis] - centroid_min[axis]) / extent) as usize;
+
+        b.min(bins - 1)
+    };
+    let mut counts = vec![0_usize; bins];
+    let mut bounds = vec![Aabb::EMPTY; bins];
+
+    for primitive in primitives.iter() {
+        let b = bin_of(primitive);
+
+        counts[b] += 1;
+        bounds[b] = Aabb::surrounding_box(&bounds[b], &primitive.bbox);
+    }
+
+    // Sweep from both ends so every candidate split costs O(1) to evaluate.
+    let mut right_area = vec![0_f64; bins];
+    let mut right_count = vec![0_usize; bins];
+    let mut accumulated = Aabb::EMPTY;
+    let mut count = 0;
+
+    for b in (1..bins).rev() {
+        accumulated = Aabb::surrounding_box(&accumulated, &bounds[b]);
+        count += counts[b];
+        right_count[b] = count;
+        right_area[b] = if count > 0 {
+            accumulated.surface_area()
+        } else {
+            0_f64
         };
-        let t = hit_left.as_ref().map(|rec| rec.t).unwrap_or(ray_t.max);
-        let hit_right = if let Some(rec) = self.right.hit(r, &Interval::new(ray_t.min, t)) {
-            Some(rec)
+    }
+
+    let total_area = bounds
+        .iter()
+        .fold(Aabb::EMPTY, |bbox, b| Aabb::surrounding_box(&bbox, b))
+        .surface_area();
+    let mut accumulated = Aabb::EMPTY;
+    let mut count = 0;
+    let mut best = (INFINITY, 0);
+
+    for b in 0..(bins - 1) {
+        accumulated = Aabb::surrounding_box(&accumulated, &bounds[b]);
+        count += counts[b];
+
+        let left_cost = if count > 0 {
+            count as f64 * accumulated.surface_area()
         } else {
-            None
+            0_f64
         };
+        let right_cost = right_count[b + 1] as f64 * right_area[b + 1];
+        // Traversal is taken to cost as much as one primitive intersection.
+        let cost = 1_f64 + (left_cost + right_cost) / total_area;
 
-        hit_left.or_else(|| hit_right)
+        if cost < best.0 {
+            best = (cost, b);
+        }
     }
 
-    fn bounding_box(&self) -> Option<Aabb> {
-        Some(self.bbox.clone())
+    if n <= options.max_leaf_size && best.0 >= n as f64 {
+        return None;
+    }
+
+    let mut mid = 0;
+
+    for i in 0..n {
+        if bin_of(&primitives[i]) <= best.1 {
+            primitives.swap(i, mid);
+            mid += 1;
+        }
+    }
+
+    if mid == 0 || mid == n {
+        Some((n / 2, axis))
+    } else {
+        Some((mid, axis))
     }
 }
+
+#[test]
+fn test_bvh_returns_closest_hit() {
+    use super::{Lambertian, Sphere};
+    use crate::{
+        sampler::{IndependentSampler, SampleStream},
+        vec3::{Color, Vec3},
+        INFINITY,
+    };
+
+    let sampler = IndependentSampler::new(0);
+    let mut samples = SampleStream::new(&sampler, 0, 0);
+
+    let mat = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let mut list = HittableList::new();
+
+    // The far sphere has the smaller centroid, so it ends up in the left child.
+    list.add(Arc::new(Sphere::new(
+        Point3::new(0_f64, 0_f64, -5_f64),
+        1_f64,
+        mat.clone(),
+    )));
+    list.add(Arc::new(Sphere::new(
+        Point3::new(0_f64, 0_f64, -2_f64),
+        0.5,
+        mat,
+    )));
+
+    let options = BvhOptions {
+        split_method: SplitMethod::Median,
+        max_leaf_size: 1,
+        ..BvhOptions::default()
+    };
+    let bvh = BvhNode::build(&list, options);
+    let r = Ray::new(Point3::zeroes(), Vec3::new(0_f64, 0_f64, -1_f64), 0_f64);
+    let rec = bvh
+        .hit(&r, &Interval::new(0.001, INFINITY), &mut samples)
+        .expect("ray should hit both spheres");
+
+    assert!((rec.t - 1.5).abs() < 1e-9);
+}
+
+#[test]
+fn test_sah_partition_separates_clusters() {
+    use super::{Lambertian, Sphere};
+    use crate::vec3::Color;
+
+    let mat = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let objects = [0_f64, 0.5, 1_f64, 100_f64, 100.5]
+        .iter()
+        .map(|&x| {
+            Arc::new(Sphere::new(Point3::new(x, 0_f64, 0_f64), 0.1, mat.clone()))
+                as Arc<dyn Hittable>
+        })
+        .collect::<Vec<_>>();
+    let mut primitives = BvhPrimitive::from_objects(&objects);
+    let (mid, axis) =
+        partition(&mut primitives, &BvhOptions::default()).expect("clusters should split");
+    let mut left = primitives[..mid]
+        .iter()
+        .map(|primitive| primitive.index)
+        .collect::<Vec<_>>();
+
+    left.sort();
+
+    assert_eq!(0, axis);
+    assert_eq!(vec![0, 1, 2], left);
+}
+
+#[test]
+fn test_bvh_matches_linear_search() {
+    use super::{Lambertian, Sphere};
+    use crate::{
+        sampler::{IndependentSampler, SampleStream},
+        vec3::{Color, Vec3},
+    };
+
+    let sampler = IndependentSampler::new(0);
+    let mut samples = SampleStream::new(&sampler, 0, 0);
+    let mat = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let mut list = HittableList::new();
+
+    for i in 0..200 {
+        let x = (i % 20) as f64;
+        let z = (i / 20) as f64 * 1.5;
+
+        list.add(Arc::new(Sphere::new(
+            Point3::new(x, 0_f64, z),
+            0.4,
+            mat.clone(),
+        )));
+    }
+
+    for split_method in [SplitMethod::Median, SplitMethod::Sah] {
+        let options = BvhOptions {
+            split_method,
+            max_leaf_size: 3,
+            bins: 8,
+        };
+        let bvh = BvhNode::build(&list, options);
+
+        for i in 0..50 {
+            let r = Ray::new(
+                Point3::new(-5_f64, 0.05 * (i % 7) as f64, -5_f64),
+                Vec3::new(1_f64, 0_f64, 0.05 * i as f64 + 0.3),
+                0_f64,
+            );
+            let expected = list
+                .hit(&r, &Interval::new(0.001, INFINITY), &mut samples)
+                .map(|rec| rec.t);
+            let result = bvh
+                .hit(&r, &Interval::new(0.001, INFINITY), &mut samples)
+                .map(|rec| rec.t);
+
+            assert_eq!(expected, result, "{:?}", split_method);
+        }
+    }
+}
+
+#[test]
+fn test_median_bvh_keeps_small_lists_in_one_leaf() {
+    use super::{Lambertian, Sphere};
+    use crate::vec3::Color;
+
+    let mat = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let mut list = HittableList::new();
+
+    for x in 0..3 {
+        list.add(Arc::new(Sphere::new(
+            Point3::new(x as f64, 0_f64, 0_f64),
+            0.4,
+            mat.clone(),
+        )));
+    }
+
+    let options = BvhOptions {
+        split_method: SplitMethod::Median,
+        ..BvhOptions::default()
+    };
+    let bvh = BvhNode::build(&list, options);
+
+    assert!(matches!(&bvh.children, BvhChildren::Leaf(objects) if objects.len() == 3));
+}
+
+#[test]
+fn test_bvh_from_empty_list() {
+    use crate::{
+        sampler::{IndependentSampler, SampleStream},
+        vec3::Vec3,
+        INFINITY,
+    };
+
+    let sampler = IndependentSampler::new(0);
+    let mut samples = SampleStream::new(&sampler, 0, 0);
+
+    let bvh = BvhNode::from(&HittableList::new());
+    let r = Ray::new(Point3::zeroes(), Vec3::new(0_f64, 0_f64, -1_f64), 0_f64);
+
+    assert!(bvh
+        .hit(&r, &Interval::new(0.001, INFINITY), &mut samples)
+        .is_none());
+}
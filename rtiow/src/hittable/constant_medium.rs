@@ -0,0 +1,142 @@
+use super::{Aabb, HitRecord, Hittable, Isotropic, Material};
+use crate::{
+    interval::Interval,
+    ray::Ray,
+    sampler::SampleStream,
+    texture::Texture,
+    vec3::{Color, Vec3},
+    INFINITY,
+};
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub struct ConstantMedium {
+    boundary: Arc<dyn Hittable>,
+    neg_inv_density: f64,
+    phase_function: Arc<dyn Material>,
+}
+
+impl ConstantMedium {
+    pub fn new(boundary: Arc<dyn Hittable>, density: f64, albedo: Color) -> Self {
+        Self {
+            boundary,
+            neg_inv_density: -1_f64 / density,
+            phase_function: Arc::new(Isotropic::new(albedo)),
+        }
+    }
+
+    pub fn from(boundary: Arc<dyn Hittable>, density: f64, tex: Arc<dyn Texture>) -> Self {
+        Self {
+            boundary,
+            neg_inv_density: -1_f64 / density,
+            phase_function: Arc::new(Isotropic::from(tex)),
+        }
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, r: &Ray, ray_t: &Interval, samples: &mut SampleStream) -> Option<HitRecord<'_>> {
+        let rec1 = self.boundary.hit(r, &Interval::UNIVERSE, samples)?;
+        let rec2 = self
+            .boundary
+            .hit(r, &Interval::new(rec1.t + 0.0001, INFINITY), samples)?;
+        let t_enter = rec1.t.max(ray_t.min).max(0_f64);
+        let t_exit = rec2.t.min(ray_t.max);
+
+        if t_enter >= t_exit {
+            return None;
+        }
+
+        let ray_length = r.direction().length();
+        let distance_inside_boundary = (t_exit - t_enter) * ray_length;
+        // `1 - u` lies in (0, 1], so the logarithm stays finite
+        let hit_distance = self.neg_inv_density * (1_f64 - samples.next_1d()).ln();
+
+        if hit_distance > distance_inside_boundary {
+            return None;
+        }
+
+        let t = t_enter + hit_distance / ray_length;
+
+        Some(HitRecord {
+            p: r.at(t),
+            normal: Vec3::new(1_f64, 0_f64, 0_f64), // arbitrary
+            mat: self.phase_function.as_ref(),
+            t,
+            u: 0_f64,
+            v: 0_f64,
+            front_face: true, // also arbitrary
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+}
+
+#[test]
+fn test_dense_medium_scatters_near_boundary() {
+    use super::{Lambertian, Sphere};
+    use crate::{
+        sampler::{IndependentSampler, SampleStream},
+        vec3::Point3,
+    };
+
+    let sampler = IndependentSampler::new(0);
+    let mut samples = SampleStream::new(&sampler, 0, 0);
+
+    let boundary: Arc<dyn Hittable> = Arc::new(Sphere::new(
+        Point3::zeroes(),
+        1_f64,
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    ));
+    let fog = ConstantMedium::new(boundary, 1e9, Color::new(0.2, 0.4, 0.9));
+    let r = Ray::new(
+        Point3::new(0_f64, 0_f64, 5_f64),
+        Vec3::new(0_f64, 0_f64, -1_f64),
+        0_f64,
+    );
+    let rec = fog
+        .hit(&r, &Interval::new(0.001, INFINITY), &mut samples)
+        .expect("a dense medium should always scatter");
+
+    assert!((rec.t - 4_f64).abs() < 1e-6);
+    assert!(fog
+        .hit(&r, &Interval::new(0.001, 3_f64), &mut samples)
+        .is_none());
+}
+
+#[test]
+fn test_medium_free_path_comes_from_sample_stream() {
+    use super::{Lambertian, Sphere};
+    use crate::{
+        sampler::{IndependentSampler, SampleStream},
+        vec3::Point3,
+    };
+
+    let boundary: Arc<dyn Hittable> = Arc::new(Sphere::new(
+        Point3::zeroes(),
+        1_f64,
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    ));
+    let fog = ConstantMedium::new(boundary, 1_f64, Color::new(0.2, 0.4, 0.9));
+    let r = Ray::new(
+        Point3::new(0_f64, 0_f64, 5_f64),
+        Vec3::new(0_f64, 0_f64, -1_f64),
+        0_f64,
+    );
+    let sampler = IndependentSampler::new(3);
+    let free_path = |index: u32| {
+        let mut samples = SampleStream::new(&sampler, 0, index);
+
+        fog.hit(&r, &Interval::new(0.001, INFINITY), &mut samples)
+            .map(|rec| rec.t)
+    };
+    let paths = (0..16).map(free_path).collect::<Vec<Option<f64>>>();
+    let hits = paths.iter().flatten().copied().collect::<Vec<f64>>();
+
+    // The same ray scatters at different depths in different samples
+    assert_eq!(paths, (0..16).map(free_path).collect::<Vec<Option<f64>>>());
+    assert!(hits.iter().any(|t| (t - hits[0]).abs() > 1e-3));
+    assert!(hits.iter().all(|t| (4_f64..6_f64).contains(t)));
+}
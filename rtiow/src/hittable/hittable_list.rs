@@ -1,8 +1,8 @@
 use super::{Aabb, HitRecord, Hittable};
-use crate::{interval::Interval, ray::Ray, vec3::Point3};
+use crate::{interval::Interval, ray::Ray, sampler::SampleStream, vec3::Point3};
 use std::sync::Arc;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct HittableList {
     pub objects: Vec<Arc<dyn Hittable>>,
     bbox: Aabb,
@@ -40,12 +40,12 @@ impl HittableList {
 }
 
 impl Hittable for HittableList {
-    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+    fn hit(&self, r: &Ray, ray_t: &Interval, samples: &mut SampleStream) -> Option<HitRecord<'_>> {
         let mut hit_anything = None;
         let mut closest_so_far = ray_t.max;
 
         for object in self.objects.iter() {
-            if let Some(rec) = object.hit(r, &Interval::new(ray_t.min, closest_so_far)) {
+            if let Some(rec) = object.hit(r, &Interval::new(ray_t.min, closest_so_far), samples) {
                 closest_so_far = rec.t;
                 hit_anything = Some(rec);
             }
@@ -0,0 +1,327 @@
+use super::{Aabb, HitRecord, Hittable};
+use crate::{
+    error::RTError,
+    interval::Interval,
+    ray::Ray,
+    sampler::SampleStream,
+    utils,
+    vec3::{self, Mat3, Mat4, Point3, Vec3},
+    INFINITY,
+};
+use anyhow::{bail, Result};
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub struct Translate {
+    object: Arc<dyn Hittable>,
+    offset: Vec3,
+    bbox: Option<Aabb>,
+}
+
+#[derive(Debug)]
+pub struct RotateY {
+    object: Arc<dyn Hittable>,
+    sin_theta: f64,
+    cos_theta: f64,
+    bbox: Option<Aabb>,
+}
+
+#[derive(Debug)]
+pub struct Transformed {
+    object: Arc<dyn Hittable>,
+    object_to_world: Mat4,
+    world_to_object: Mat4,
+    normal_matrix: Mat3,
+    bbox: Option<Aabb>,
+}
+
+impl Translate {
+    pub fn new(object: Arc<dyn Hittable>, offset: Vec3) -> Self {
+        let bbox = object.bounding_box().map(|bbox| bbox + offset);
+
+        Self {
+            object,
+            offset,
+            bbox,
+        }
+    }
+}
+
+impl Hittable for Translate {
+    fn hit(&self, r: &Ray, ray_t: &Interval, samples: &mut SampleStream) -> Option<HitRecord<'_>> {
+        let offset_r = Ray::new(*r.origin() - self.offset, *r.direction(), *r.time());
+        let mut rec = self.object.hit(&offset_r, ray_t, samples)?;
+
+        rec.p += self.offset;
+
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bbox.clone()
+    }
+}
+
+impl RotateY {
+    pub fn new(object: Arc<dyn Hittable>, angle: f64) -> Self {
+        let radians = utils::degrees_to_radians(angle);
+        let sin_theta = radians.sin();
+        let cos_theta = radians.cos();
+        let bbox = object.bounding_box().map(|bbox| {
+            let mut min = Point3::new(INFINITY, INFINITY, INFINITY);
+            let mut max = Point3::new(-INFINITY, -INFINITY, -INFINITY);
+
+            for ijk in 0..8 {
+                let i = (ijk & 1) as f64;
+                let j = ((ijk >> 1) & 1) as f64;
+                let k = ((ijk >> 2) & 1) as f64;
+                let x = i * bbox.axis_interval(0).max + (1_f64 - i) * bbox.axis_interval(0).min;
+                let y = j * bbox.axis_interval(1).max + (1_f64 - j) * bbox.axis_interval(1).min;
+                let z = k * bbox.axis_interval(2).max + (1_f64 - k) * bbox.axis_interval(2).min;
+                let tester = Vec3::new(
+                    cos_theta * x + sin_theta * z,
+                    y,
+                    -sin_theta * x + cos_theta * z,
+                );
+
+                min = Point3::new(
+                    min.x().min(tester.x()),
+                    min.y().min(tester.y()),
+                    min.z().min(tester.z()),
+                );
+                max = Point3::new(
+                    max.x().max(tester.x()),
+                    max.y().max(tester.y()),
+                    max.z().max(tester.z()),
+                );
+            }
+
+            Aabb::new(min, max)
+        });
+
+        Self {
+            object,
+            sin_theta,
+            cos_theta,
+            bbox,
+        }
+    }
+
+    fn world_to_object(&self, v: &Vec3) -> Vec3 {
+        Vec3::new(
+            self.cos_theta * v.x() - self.sin_theta * v.z(),
+            v.y(),
+            self.sin_theta * v.x() + self.cos_theta * v.z(),
+        )
+    }
+
+    fn object_to_world(&self, v: &Vec3) -> Vec3 {
+        Vec3::new(
+            self.cos_theta * v.x() + self.sin_theta * v.z(),
+            v.y(),
+            -self.sin_theta * v.x() + self.cos_theta * v.z(),
+        )
+    }
+}
+
+impl Hittable for RotateY {
+    fn hit(&self, r: &Ray, ray_t: &Interval, samples: &mut SampleStream) -> Option<HitRecord<'_>> {
+        let rotated_r = Ray::new(
+            self.world_to_object(r.origin()),
+            self.world_to_object(r.direction()),
+            *r.time(),
+        );
+        let mut rec = self.object.hit(&rotated_r, ray_t, samples)?;
+
+        rec.p = self.object_to_world(&rec.p);
+        rec.normal = self.object_to_world(&rec.normal);
+
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bbox.clone()
+    }
+}
+
+impl Transformed {
+    pub fn new(object: Arc<dyn Hittable>, object_to_world: Mat4) -> Result<Self> {
+        let world_to_object = match object_to_world.inverse() {
+            Some(inverse) => inverse,
+            None => bail!(RTError::NonInvertibleTransform),
+        };
+        let normal_matrix = world_to_object.upper_left().transpose();
+        let bbox = object.bounding_box().map(|bbox| {
+            let mut min = Point3::new(INFINITY, INFINITY, INFINITY);
+            let mut max = Point3::new(-INFINITY, -INFINITY, -INFINITY);
+
+            for ijk in 0..8 {
+                let corner = Point3::new(
+                    if ijk & 1 == 0 {
+                        bbox.axis_interval(0).min
+                    } else {
+                        bbox.axis_interval(0).max
+                    },
+                    if (ijk >> 1) & 1 == 0 {
+                        bbox.axis_interval(1).min
+                    } else {
+                        bbox.axis_interval(1).max
+                    },
+                    if (ijk >> 2) & 1 == 0 {
+                        bbox.axis_interval(2).min
+                    } else {
+                        bbox.axis_interval(2).max
+                    },
+                );
+                let tester = object_to_world.transform_point(&corner);
+
+                min = Point3::new(
+                    min.x().min(tester.x()),
+                    min.y().min(tester.y()),
+                    min.z().min(tester.z()),
+                );
+                max = Point3::new(
+                    max.x().max(tester.x()),
+                    max.y().max(tester.y()),
+                    max.z().max(tester.z()),
+                );
+            }
+
+            Aabb::new(min, max)
+        });
+
+        Ok(Self {
+            object,
+            object_to_world,
+            world_to_object,
+            normal_matrix,
+            bbox,
+        })
+    }
+}
+
+impl Hittable for Transformed {
+    fn hit(&self, r: &Ray, ray_t: &Interval, samples: &mut SampleStream) -> Option<HitRecord<'_>> {
+        // Affine maps keep the ray parameter, so `t` is valid in both spaces.
+        let object_r = Ray::new(
+            self.world_to_object.transform_point(r.origin()),
+            self.world_to_object.transform_vector(r.direction()),
+            *r.time(),
+        );
+        let mut rec = self.object.hit(&object_r, ray_t, samples)?;
+
+        rec.p = self.object_to_world.transform_point(&rec.p);
+        rec.normal = vec3::unit_vector(&(self.normal_matrix * rec.normal));
+
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bbox.clone()
+    }
+}
+
+#[test]
+fn test_translate_moves_hit_and_bounding_box() {
+    use super::{Lambertian, Sphere};
+    use crate::{
+        sampler::{IndependentSampler, SampleStream},
+        vec3::Color,
+    };
+
+    let sampler = IndependentSampler::new(0);
+    let mut samples = SampleStream::new(&sampler, 0, 0);
+
+    let sphere: Arc<dyn Hittable> = Arc::new(Sphere::new(
+        Point3::zeroes(),
+        1_f64,
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    ));
+    let moved = Translate::new(sphere, Vec3::new(10_f64, 0_f64, 0_f64));
+    let r = Ray::new(
+        Point3::new(10_f64, 0_f64, 5_f64),
+        Vec3::new(0_f64, 0_f64, -1_f64),
+        0_f64,
+    );
+    let rec = moved
+        .hit(&r, &Interval::new(0.001, INFINITY), &mut samples)
+        .expect("ray should hit the translated sphere");
+    let bbox = moved.bounding_box().expect("translated sphere has a box");
+
+    assert_eq!(Point3::new(10_f64, 0_f64, 1_f64), rec.p);
+    assert_eq!(9_f64, bbox.axis_interval(0).min);
+    assert_eq!(11_f64, bbox.axis_interval(0).max);
+}
+
+#[test]
+fn test_rotate_y_transforms_hit_and_bounding_box() {
+    use super::{Lambertian, Quad};
+    use crate::{
+        sampler::{IndependentSampler, SampleStream},
+        vec3::Color,
+    };
+
+    // Unit quad in the z = 0 plane facing +z; rotating by 90 degrees makes it face +x.
+
+    let sampler = IndependentSampler::new(0);
+    let mut samples = SampleStream::new(&sampler, 0, 0);
+
+    let quad: Arc<dyn Hittable> = Arc::new(Quad::new(
+        Point3::zeroes(),
+        Vec3::new(1_f64, 0_f64, 0_f64),
+        Vec3::new(0_f64, 1_f64, 0_f64),
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    ));
+    let rotated = RotateY::new(quad, 90_f64);
+    let r = Ray::new(
+        Point3::new(5_f64, 0.5, -0.5),
+        Vec3::new(-1_f64, 0_f64, 0_f64),
+        0_f64,
+    );
+    let rec = rotated
+        .hit(&r, &Interval::new(0.001, INFINITY), &mut samples)
+        .expect("ray should hit the rotated quad");
+    let bbox = rotated.bounding_box().expect("rotated quad has a box");
+
+    assert!((rec.t - 5_f64).abs() < 1e-9);
+    assert!((rec.normal.x() - 1_f64).abs() < 1e-9);
+    assert!((bbox.axis_interval(2).min + 1_f64).abs() < 1e-9);
+    assert!(bbox.axis_interval(0).size() < 1e-3);
+}
+
+#[test]
+fn test_transformed_scales_hit_and_normal() {
+    use super::{Lambertian, Sphere};
+    use crate::{
+        sampler::{IndependentSampler, SampleStream},
+        vec3::Color,
+    };
+
+    let sampler = IndependentSampler::new(0);
+    let mut samples = SampleStream::new(&sampler, 0, 0);
+
+    let sphere: Arc<dyn Hittable> = Arc::new(Sphere::new(
+        Point3::zeroes(),
+        1_f64,
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    ));
+    let transform = Mat4::translation(&Vec3::new(0_f64, 5_f64, 0_f64))
+        * Mat4::scaling(&Vec3::new(4_f64, 1_f64, 1_f64));
+    let ellipsoid = Transformed::new(sphere.clone(), transform).expect("transform is invertible");
+    let r = Ray::new(
+        Point3::new(10_f64, 5_f64, 0_f64),
+        Vec3::new(-1_f64, 0_f64, 0_f64),
+        0_f64,
+    );
+    let rec = ellipsoid
+        .hit(&r, &Interval::new(0.001, INFINITY), &mut samples)
+        .expect("ray should hit the ellipsoid");
+    let bbox = ellipsoid.bounding_box().expect("ellipsoid has a box");
+
+    assert!((rec.t - 6_f64).abs() < 1e-9);
+    assert!((rec.p - Point3::new(4_f64, 5_f64, 0_f64)).near_zero());
+    assert!((rec.normal - Vec3::new(1_f64, 0_f64, 0_f64)).near_zero());
+    assert!((bbox.axis_interval(0).max - 4_f64).abs() < 1e-9);
+    assert!((bbox.axis_interval(1).min - 4_f64).abs() < 1e-9);
+    assert!(Transformed::new(sphere, Mat4::scaling(&Vec3::zeroes())).is_err());
+}
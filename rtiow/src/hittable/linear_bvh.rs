@@ -0,0 +1,235 @@
+use super::{bvh, Aabb, BvhOptions, BvhPrimitive, HitRecord, Hittable, HittableList};
+use crate::{interval::Interval, ray::Ray, sampler::SampleStream};
+use std::sync::Arc;
+
+/// A BVH flattened into a single `Vec` in depth-first order, so the first
+/// child of an interior node is always the node right after it.
+#[derive(Debug)]
+pub struct LinearBvh {
+    objects: Vec<Arc<dyn Hittable>>,
+    nodes: Vec<LinearBvhNode>,
+    depth: usize,
+}
+
+#[derive(Debug)]
+struct LinearBvhNode {
+    bbox: Aabb,
+    /// First object for a leaf, second child for an interior node.
+    offset: usize,
+    /// Number of objects in a leaf; zero marks an interior node.
+    count: usize,
+    axis: usize,
+}
+
+impl LinearBvh {
+    const STACK_SIZE: usize = 64;
+
+    pub fn new(list: &HittableList, options: BvhOptions) -> Self {
+        let mut primitives = BvhPrimitive::from_objects(&list.objects);
+        let mut nodes = Vec::with_capacity(2 * primitives.len());
+        let depth = Self::build(&mut nodes, &mut primitives, 0, &options);
+        let objects = primitives
+            .iter()
+            .map(|primitive| list.objects[primitive.index].clone())
+            .collect();
+
+        Self {
+            objects,
+            nodes,
+            depth,
+        }
+    }
+
+    pub fn from(list: &HittableList) -> Self {
+        Self::new(list, BvhOptions::default())
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Appends the subtree over `primitives` to `nodes` and returns its depth.
+    /// `first` is the position of `primitives[0]` in the final object order.
+    fn build(
+        nodes: &mut Vec<LinearBvhNode>,
+        primitives: &mut [BvhPrimitive],
+        first: usize,
+        options: &BvhOptions,
+    ) -> usize {
+        let bbox = primitives.iter().fold(Aabb::EMPTY, |bbox, primitive| {
+            Aabb::surrounding_box(&bbox, &primitive.bbox)
+        });
+        let index = nodes.len();
+
+        nodes.push(LinearBvhNode {
+            bbox,
+            offset: first,
+            count: primitives.len(),
+            axis: 0,
+        });
+
+        match bvh::partition(primitives, options) {
+            None => 1,
+            Some((mid, axis)) => {
+                let (left, right) = primitives.split_at_mut(mid);
+                let left_depth = Self::build(nodes, left, first, options);
+                let offset = nodes.len();
+                let right_depth = Self::build(nodes, right, first + mid, options);
+
+                nodes[index].offset = offset;
+                nodes[index].count = 0;
+                nodes[index].axis = axis;
+
+                1 + left_depth.max(right_depth)
+            }
+        }
+    }
+
+    fn traverse(
+        &self,
+        r: &Ray,
+        ray_t: &Interval,
+        samples: &mut SampleStream,
+        stack: &mut [usize],
+    ) -> Option<HitRecord<'_>> {
+        let mut hit_anything = None;
+        let mut closest_so_far = ray_t.max;
+        let mut stack_size = 0;
+        let mut index = 0;
+
+        loop {
+            let node = &self.nodes[index];
+
+            if node.bbox.hit(r, &Interval::new(ray_t.min, closest_so_far)) {
+                if node.count > 0 {
+                    for object in self.objects[node.offset..(node.offset + node.count)].iter() {
+                        if let Some(rec) =
+                            object.hit(r, &Interval::new(ray_t.min, closest_so_far), samples)
+                        {
+                            closest_so_far = rec.t;
+                            hit_anything = Some(rec);
+                        }
+                    }
+                } else {
+                    // Visit the child on the near side of the split first so the
+                    // far one can usually be culled by the shorter interval.
+                    let (near, far) = if r.direction()[node.axis] < 0_f64 {
+                        (node.offset, index + 1)
+                    } else {
+                        (index + 1, node.offset)
+                    };
+
+                    stack[stack_size] = far;
+                    stack_size += 1;
+                    index = near;
+                    continue;
+                }
+            }
+            if stack_size == 0 {
+                break;
+            }
+
+            stack_size -= 1;
+            index = stack[stack_size];
+        }
+
+        hit_anything
+    }
+}
+
+impl Hittable for LinearBvh {
+    fn hit(&self, r: &Ray, ray_t: &Interval, samples: &mut SampleStream) -> Option<HitRecord<'_>> {
+        if self.objects.is_empty() {
+            return None;
+        }
+
+        if self.depth <= Self::STACK_SIZE {
+            self.traverse(r, ray_t, samples, &mut [0; Self::STACK_SIZE])
+        } else {
+            self.traverse(r, ray_t, samples, &mut vec![0; self.depth])
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(
+            self.nodes
+                .first()
+                .map(|node| node.bbox.clone())
+                .unwrap_or(Aabb::EMPTY),
+        )
+    }
+}
+
+#[test]
+fn test_linear_bvh_matches_linear_search() {
+    use super::{Lambertian, Sphere, SplitMethod};
+    use crate::{
+        sampler::{IndependentSampler, SampleStream},
+        vec3::{Color, Point3, Vec3},
+        INFINITY,
+    };
+
+    let sampler = IndependentSampler::new(0);
+    let mut samples = SampleStream::new(&sampler, 0, 0);
+
+    let mat = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let mut list = HittableList::new();
+
+    for i in 0..300 {
+        let x = (i % 15) as f64;
+        let y = ((i / 15) % 4) as f64;
+        let z = (i / 60) as f64 * 1.5;
+
+        list.add(Arc::new(Sphere::new(
+            Point3::new(x, y, z),
+            0.3 + 0.02 * (i % 5) as f64,
+            mat.clone(),
+        )));
+    }
+
+    for split_method in [SplitMethod::Median, SplitMethod::Sah] {
+        let bvh = LinearBvh::new(
+            &list,
+            BvhOptions {
+                split_method,
+                ..BvhOptions::default()
+            },
+        );
+
+        for i in 0..60 {
+            let r = Ray::new(
+                Point3::new(20_f64, 0.05 * (i % 9) as f64, -3_f64),
+                Vec3::new(-1_f64, 0.02 * (i % 4) as f64, 0.04 * i as f64),
+                0_f64,
+            );
+            let expected = list
+                .hit(&r, &Interval::new(0.001, INFINITY), &mut samples)
+                .map(|rec| rec.t);
+            let result = bvh
+                .hit(&r, &Interval::new(0.001, INFINITY), &mut samples)
+                .map(|rec| rec.t);
+
+            assert_eq!(expected, result);
+        }
+    }
+}
+
+#[test]
+fn test_linear_bvh_empty_list() {
+    use crate::{
+        sampler::{IndependentSampler, SampleStream},
+        vec3::{Point3, Vec3},
+        INFINITY,
+    };
+
+    let sampler = IndependentSampler::new(0);
+    let mut samples = SampleStream::new(&sampler, 0, 0);
+
+    let bvh = LinearBvh::from(&HittableList::new());
+    let r = Ray::new(Point3::zeroes(), Vec3::new(0_f64, 0_f64, -1_f64), 0_f64);
+
+    assert_eq!(1, bvh.num_nodes());
+    assert!(bvh
+        .hit(&r, &Interval::new(0.001, INFINITY), &mut samples)
+        .is_none());
+}
@@ -1,9 +1,9 @@
 use super::{HitRecord, Material};
 use crate::{
     ray::Ray,
+    sampler::SampleStream,
     texture::{SolidColor, Texture},
-    utils,
-    vec3::{self, Color},
+    vec3::{self, Color, Point3},
 };
 use std::sync::Arc;
 
@@ -23,6 +23,16 @@ pub struct Dielectric {
     refraction_index: f64,
 }
 
+#[derive(Debug, Clone)]
+pub struct DiffuseLight {
+    tex: Arc<dyn Texture>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Isotropic {
+    tex: Arc<dyn Texture>,
+}
+
 impl Lambertian {
     pub fn new(albedo: Color) -> Self {
         Self {
@@ -36,9 +46,14 @@ impl Lambertian {
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        samples: &mut SampleStream,
+    ) -> Option<(Color, Ray)> {
         let scatter_direction = {
-            let scatter_direction = rec.normal + vec3::random_unit_vector();
+            let scatter_direction = rec.normal + vec3::sample_unit_vector(samples.next_2d());
 
             if scatter_direction.near_zero() {
                 rec.normal
@@ -51,6 +66,10 @@ impl Material for Lambertian {
 
         Some((attenuation, scattered))
     }
+
+    fn albedo(&self, rec: &HitRecord) -> Color {
+        self.tex.value(rec.u, rec.v, &rec.p)
+    }
 }
 
 impl Metal {
@@ -60,11 +79,17 @@ impl Metal {
 }
 
 impl Material for Metal {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        samples: &mut SampleStream,
+    ) -> Option<(Color, Ray)> {
         let reflected = {
             let reflected = vec3::reflect(r_in.direction(), &rec.normal);
 
-            vec3::unit_vector(&reflected) + (self.fuzz * vec3::random_unit_vector())
+            vec3::unit_vector(&reflected)
+                + (self.fuzz * vec3::sample_unit_vector(samples.next_2d()))
         };
         let scattered = Ray::new(rec.p, reflected, *r_in.time());
         let attenuation = self.albedo;
@@ -75,6 +100,10 @@ impl Material for Metal {
             None
         }
     }
+
+    fn albedo(&self, _rec: &HitRecord) -> Color {
+        self.albedo
+    }
 }
 
 impl Dielectric {
@@ -90,7 +119,12 @@ impl Dielectric {
 }
 
 impl Material for Dielectric {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        samples: &mut SampleStream,
+    ) -> Option<(Color, Ray)> {
         let attenuation = Color::new(1_f64, 1_f64, 1_f64);
         let refraction_index = if rec.front_face {
             1_f64 / self.refraction_index
@@ -102,7 +136,7 @@ impl Material for Dielectric {
         let sin_theta = (1_f64 - cos_theta.powi(2)).sqrt();
         let cannot_refract = refraction_index * sin_theta > 1_f64;
         let direction = if cannot_refract
-            || (Dielectric::reflectance(cos_theta, refraction_index)) > utils::random()
+            || (Dielectric::reflectance(cos_theta, refraction_index)) > samples.next_1d()
         {
             vec3::reflect(&unit_direction, &rec.normal)
         } else {
@@ -112,4 +146,99 @@ impl Material for Dielectric {
 
         Some((attenuation, scattered))
     }
+
+    /// Glass transmits everything, so its albedo is white.
+    fn albedo(&self, _rec: &HitRecord) -> Color {
+        Color::new(1_f64, 1_f64, 1_f64)
+    }
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Color) -> Self {
+        Self {
+            tex: Arc::new(SolidColor::new(emit)),
+        }
+    }
+
+    pub fn from(tex: Arc<dyn Texture>) -> Self {
+        Self { tex }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn emitted(&self, u: f64, v: f64, p: &Point3) -> Color {
+        self.tex.value(u, v, p)
+    }
+
+    fn albedo(&self, rec: &HitRecord) -> Color {
+        self.tex.value(rec.u, rec.v, &rec.p)
+    }
+}
+
+impl Isotropic {
+    pub fn new(albedo: Color) -> Self {
+        Self {
+            tex: Arc::new(SolidColor::new(albedo)),
+        }
+    }
+
+    pub fn from(tex: Arc<dyn Texture>) -> Self {
+        Self { tex }
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        samples: &mut SampleStream,
+    ) -> Option<(Color, Ray)> {
+        let scattered = Ray::new(
+            rec.p,
+            vec3::sample_unit_vector(samples.next_2d()),
+            *r_in.time(),
+        );
+        let attenuation = self.tex.value(rec.u, rec.v, &rec.p);
+
+        Some((attenuation, scattered))
+    }
+
+    fn albedo(&self, rec: &HitRecord) -> Color {
+        self.tex.value(rec.u, rec.v, &rec.p)
+    }
+}
+
+#[test]
+fn test_diffuse_light_emits_without_scattering() {
+    use crate::sampler::IndependentSampler;
+
+    let light = DiffuseLight::new(Color::new(4_f64, 4_f64, 4_f64));
+    let lambertian = Lambertian::new(Color::new(0.5, 0.5, 0.5));
+    let p = Point3::zeroes();
+    let rec = HitRecord {
+        p,
+        normal: vec3::Vec3::new(0_f64, 1_f64, 0_f64),
+        mat: &light,
+        t: 1_f64,
+        u: 0_f64,
+        v: 0_f64,
+        front_face: true,
+    };
+    let r_in = Ray::new(
+        Point3::new(0_f64, 1_f64, 0_f64),
+        vec3::Vec3::new(0_f64, -1_f64, 0_f64),
+        0_f64,
+    );
+
+    assert_eq!(
+        Color::new(4_f64, 4_f64, 4_f64),
+        light.emitted(0_f64, 0_f64, &p)
+    );
+    let sampler = IndependentSampler::new(0);
+
+    assert!(light
+        .scatter(&r_in, &rec, &mut SampleStream::new(&sampler, 0, 0))
+        .is_none());
+    assert_eq!(Color::zeroes(), lambertian.emitted(0_f64, 0_f64, &p));
 }
@@ -1,21 +1,32 @@
 use crate::{
     interval::Interval,
     ray::Ray,
+    sampler::SampleStream,
     vec3::{self, Color, Point3, Vec3},
 };
 use std::fmt::Debug;
 
 mod aabb;
 mod bvh;
+mod constant_medium;
 mod hittable_list;
+mod instance;
+mod linear_bvh;
 mod material;
+mod quad;
 mod sphere;
+mod triangle;
 
 pub use aabb::*;
 pub use bvh::*;
+pub use constant_medium::*;
 pub use hittable_list::*;
+pub use instance::*;
+pub use linear_bvh::*;
 pub use material::*;
+pub use quad::*;
 pub use sphere::*;
+pub use triangle::*;
 
 #[derive(Debug)]
 pub struct HitRecord<'a> {
@@ -40,7 +51,14 @@ impl<'a> HitRecord<'a> {
 }
 
 pub trait Hittable: Debug + Sync + Send {
-    fn hit(&self, _r: &Ray, _ray_t: &Interval) -> Option<HitRecord> {
+    /// `samples` is the sample stream of the camera sample being traced,
+    /// for objects whose hits are random themselves, such as volumes.
+    fn hit(
+        &self,
+        _r: &Ray,
+        _ray_t: &Interval,
+        _samples: &mut SampleStream,
+    ) -> Option<HitRecord<'_>> {
         None
     }
 
@@ -50,7 +68,45 @@ pub trait Hittable: Debug + Sync + Send {
 }
 
 pub trait Material: Sync + Send + Debug {
-    fn scatter(&self, _r_in: &Ray, _rec: &HitRecord) -> Option<(Color, Ray)> {
+    fn emitted(&self, _u: f64, _v: f64, _p: &Point3) -> Color {
+        Color::zeroes()
+    }
+
+    fn scatter(
+        &self,
+        _r_in: &Ray,
+        _rec: &HitRecord,
+        _samples: &mut SampleStream,
+    ) -> Option<(Color, Ray)> {
         None
     }
+
+    /// Base color of the surface at the hit point, as written to the albedo
+    /// AOV. Unlike the attenuation returned by `scatter` it is free of
+    /// randomness.
+    fn albedo(&self, _rec: &HitRecord) -> Color {
+        Color::zeroes()
+    }
+}
+
+/// A unit sphere three units in front of the default camera, resting on a
+/// large ground sphere. Tests add whatever else they need.
+#[cfg(test)]
+pub(crate) fn test_world() -> HittableList {
+    use std::sync::Arc;
+
+    let mut world = HittableList::new();
+
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0_f64, 0_f64, -3_f64),
+        1_f64,
+        Arc::new(Lambertian::new(Color::new(0.2, 0.4, 0.6))),
+    )));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0_f64, -101_f64, -3_f64),
+        100_f64,
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    )));
+
+    world
 }
@@ -0,0 +1,220 @@
+use super::{Aabb, HitRecord, Hittable, HittableList, Material};
+use crate::{
+    interval::Interval,
+    ray::Ray,
+    sampler::SampleStream,
+    vec3::{self, Point3, Vec3},
+};
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub struct Quad {
+    q: Point3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    mat: Arc<dyn Material>,
+    bbox: Aabb,
+    normal: Vec3,
+    d: f64,
+}
+
+impl Quad {
+    pub fn new(q: Point3, u: Vec3, v: Vec3, mat: Arc<dyn Material>) -> Self {
+        let n = vec3::cross(&u, &v);
+        let normal = vec3::unit_vector(&n);
+        let d = vec3::dot(&normal, &q);
+        let w = n / vec3::dot(&n, &n);
+        let bbox = Aabb::surrounding_box(&Aabb::new(q, q + u + v), &Aabb::new(q + u, q + v));
+
+        Self {
+            q,
+            u,
+            v,
+            w,
+            mat,
+            bbox,
+            normal,
+            d,
+        }
+    }
+
+    fn is_interior(a: f64, b: f64) -> Option<[f64; 2]> {
+        let unit_interval = Interval::new(0_f64, 1_f64);
+
+        if unit_interval.contains(a) && unit_interval.contains(b) {
+            Some([a, b])
+        } else {
+            None
+        }
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, r: &Ray, ray_t: &Interval, _samples: &mut SampleStream) -> Option<HitRecord<'_>> {
+        let denom = vec3::dot(&self.normal, r.direction());
+
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = (self.d - vec3::dot(&self.normal, r.origin())) / denom;
+
+        if !ray_t.contains(t) {
+            return None;
+        }
+
+        let intersection = r.at(t);
+        let planar_hitpt_vector = intersection - self.q;
+        let alpha = vec3::dot(&self.w, &vec3::cross(&planar_hitpt_vector, &self.v));
+        let beta = vec3::dot(&self.w, &vec3::cross(&self.u, &planar_hitpt_vector));
+        let uv = Self::is_interior(alpha, beta)?;
+        let mut hit = HitRecord {
+            p: intersection,
+            normal: self.normal,
+            t,
+            u: uv[0],
+            v: uv[1],
+            front_face: false,
+            mat: self.mat.as_ref(),
+        };
+
+        hit.set_face_normal(r, &self.normal);
+
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bbox.clone())
+    }
+}
+
+pub fn make_box(a: &Point3, b: &Point3, mat: Arc<dyn Material>) -> HittableList {
+    let mut sides = HittableList::new();
+    let min = Point3::new(a.x().min(b.x()), a.y().min(b.y()), a.z().min(b.z()));
+    let max = Point3::new(a.x().max(b.x()), a.y().max(b.y()), a.z().max(b.z()));
+    let dx = Vec3::new(max.x() - min.x(), 0_f64, 0_f64);
+    let dy = Vec3::new(0_f64, max.y() - min.y(), 0_f64);
+    let dz = Vec3::new(0_f64, 0_f64, max.z() - min.z());
+
+    // front
+    sides.add(Arc::new(Quad::new(
+        Point3::new(min.x(), min.y(), max.z()),
+        dx,
+        dy,
+        mat.clone(),
+    )));
+    // right
+    sides.add(Arc::new(Quad::new(
+        Point3::new(max.x(), min.y(), max.z()),
+        -dz,
+        dy,
+        mat.clone(),
+    )));
+    // back
+    sides.add(Arc::new(Quad::new(
+        Point3::new(max.x(), min.y(), min.z()),
+        -dx,
+        dy,
+        mat.clone(),
+    )));
+    // left
+    sides.add(Arc::new(Quad::new(
+        Point3::new(min.x(), min.y(), min.z()),
+        dz,
+        dy,
+        mat.clone(),
+    )));
+    // top
+    sides.add(Arc::new(Quad::new(
+        Point3::new(min.x(), max.y(), max.z()),
+        dx,
+        -dz,
+        mat.clone(),
+    )));
+    // bottom
+    sides.add(Arc::new(Quad::new(
+        Point3::new(min.x(), min.y(), min.z()),
+        dx,
+        dz,
+        mat,
+    )));
+
+    sides
+}
+
+#[test]
+fn test_quad_hit_uv() {
+    use super::Lambertian;
+    use crate::{
+        sampler::{IndependentSampler, SampleStream},
+        vec3::Color,
+    };
+
+    let sampler = IndependentSampler::new(0);
+    let mut samples = SampleStream::new(&sampler, 0, 0);
+
+    let quad = Quad::new(
+        Point3::new(-1_f64, -1_f64, 0_f64),
+        Vec3::new(2_f64, 0_f64, 0_f64),
+        Vec3::new(0_f64, 4_f64, 0_f64),
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    );
+    let r = Ray::new(
+        Point3::new(0.5, 0_f64, 5_f64),
+        Vec3::new(0_f64, 0_f64, -1_f64),
+        0_f64,
+    );
+    let rec = quad
+        .hit(&r, &Interval::new(0.001, crate::INFINITY), &mut samples)
+        .expect("ray should hit the quad");
+
+    assert_eq!(5_f64, rec.t);
+    assert_eq!(0.75, rec.u);
+    assert_eq!(0.25, rec.v);
+    assert!(rec.front_face);
+}
+
+#[test]
+fn test_quad_miss_outside() {
+    use super::Lambertian;
+    use crate::{
+        sampler::{IndependentSampler, SampleStream},
+        vec3::Color,
+    };
+
+    let sampler = IndependentSampler::new(0);
+    let mut samples = SampleStream::new(&sampler, 0, 0);
+
+    let quad = Quad::new(
+        Point3::new(-1_f64, -1_f64, 0_f64),
+        Vec3::new(2_f64, 0_f64, 0_f64),
+        Vec3::new(0_f64, 2_f64, 0_f64),
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    );
+    let r = Ray::new(
+        Point3::new(1.5, 0_f64, 5_f64),
+        Vec3::new(0_f64, 0_f64, -1_f64),
+        0_f64,
+    );
+
+    assert!(quad
+        .hit(&r, &Interval::new(0.001, crate::INFINITY), &mut samples)
+        .is_none());
+}
+
+#[test]
+fn test_quad_bounding_box_is_padded() {
+    use super::Lambertian;
+    use crate::vec3::Color;
+
+    let quad = Quad::new(
+        Point3::zeroes(),
+        Vec3::new(1_f64, 0_f64, 0_f64),
+        Vec3::new(0_f64, 1_f64, 0_f64),
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    );
+    let bbox = quad.bounding_box().expect("quad has a bounding box");
+
+    assert!(bbox.axis_interval(2).size() > 0_f64);
+}
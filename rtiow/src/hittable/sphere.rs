@@ -2,6 +2,7 @@ use super::{Aabb, HitRecord, Hittable, Material};
 use crate::{
     interval::Interval,
     ray::Ray,
+    sampler::SampleStream,
     vec3::{self, Point3, Vec3},
     PI,
 };
@@ -38,7 +39,7 @@ impl Sphere {
         }
     }
 
-    fn get_sphere_uv(p: &Point3) -> [f64; 2] {
+    pub(crate) fn get_sphere_uv(p: &Point3) -> [f64; 2] {
         let theta = (-p.y()).acos();
         let phi = (-p.z()).atan2(p.x()) + PI;
         let u = phi / (2_f64 * PI);
@@ -49,7 +50,7 @@ impl Sphere {
 }
 
 impl Hittable for Sphere {
-    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+    fn hit(&self, r: &Ray, ray_t: &Interval, _samples: &mut SampleStream) -> Option<HitRecord<'_>> {
         let current_center = self.center.at(*r.time());
         let oc = current_center - r.origin();
         let a = r.direction().length_squared();
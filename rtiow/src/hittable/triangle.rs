@@ -0,0 +1,371 @@
+use super::{Aabb, HitRecord, Hittable, HittableList, LinearBvh, Material};
+use crate::{
+    error::RTError,
+    interval::Interval,
+    ray::Ray,
+    sampler::SampleStream,
+    vec3::{self, Point3, Vec3},
+};
+use anyhow::{bail, Result};
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub struct Triangle {
+    vertices: [Point3; 3],
+    normal: Vec3,
+    mat: Arc<dyn Material>,
+    bbox: Aabb,
+}
+
+#[derive(Debug)]
+pub struct TriangleMesh {
+    mesh: Arc<MeshData>,
+    bvh: LinearBvh,
+}
+
+#[derive(Debug)]
+struct MeshData {
+    positions: Vec<Point3>,
+    normals: Option<Vec<Vec3>>,
+    uvs: Option<Vec<[f64; 2]>>,
+    indices: Vec<[usize; 3]>,
+    mat: Arc<dyn Material>,
+}
+
+#[derive(Debug)]
+struct MeshTriangle {
+    mesh: Arc<MeshData>,
+    index: usize,
+}
+
+impl Triangle {
+    const DELTA_PARALLEL: f64 = 1e-12;
+
+    pub fn new(a: Point3, b: Point3, c: Point3, mat: Arc<dyn Material>) -> Self {
+        let normal = vec3::unit_vector(&vec3::cross(&(b - a), &(c - a)));
+        let bbox = Aabb::surrounding_box(&Aabb::new(a, b), &Aabb::new(a, c));
+
+        Self {
+            vertices: [a, b, c],
+            normal,
+            mat,
+            bbox,
+        }
+    }
+
+    /// Möller–Trumbore intersection returning `[t, b1, b2]`, where `b1` and `b2`
+    /// are the barycentric weights of the second and third vertices.
+    fn intersect(r: &Ray, ray_t: &Interval, vertices: [&Point3; 3]) -> Option<[f64; 3]> {
+        let edge1 = *vertices[1] - vertices[0];
+        let edge2 = *vertices[2] - vertices[0];
+        let pvec = vec3::cross(r.direction(), &edge2);
+        let det = vec3::dot(&edge1, &pvec);
+
+        if det.abs() < Self::DELTA_PARALLEL {
+            return None;
+        }
+
+        let inv_det = 1_f64 / det;
+        let tvec = *r.origin() - vertices[0];
+        let b1 = vec3::dot(&tvec, &pvec) * inv_det;
+
+        if !(0_f64..=1_f64).contains(&b1) {
+            return None;
+        }
+
+        let qvec = vec3::cross(&tvec, &edge1);
+        let b2 = vec3::dot(r.direction(), &qvec) * inv_det;
+
+        if b2 < 0_f64 || b1 + b2 > 1_f64 {
+            return None;
+        }
+
+        let t = vec3::dot(&edge2, &qvec) * inv_det;
+
+        if !ray_t.surrounds(t) {
+            return None;
+        }
+
+        Some([t, b1, b2])
+    }
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, r: &Ray, ray_t: &Interval, _samples: &mut SampleStream) -> Option<HitRecord<'_>> {
+        let [t, b1, b2] = Self::intersect(
+            r,
+            ray_t,
+            [&self.vertices[0], &self.vertices[1], &self.vertices[2]],
+        )?;
+        let mut rec = HitRecord {
+            p: r.at(t),
+            normal: self.normal,
+            mat: self.mat.as_ref(),
+            t,
+            u: b1,
+            v: b2,
+            front_face: false,
+        };
+
+        rec.set_face_normal(r, &self.normal);
+
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bbox.clone())
+    }
+}
+
+impl TriangleMesh {
+    /// Builds a mesh over shared vertex buffers. `normals` and `uvs`, when
+    /// given, are indexed by the same vertex indices as `positions`.
+    pub fn new(
+        positions: Vec<Point3>,
+        indices: Vec<[usize; 3]>,
+        normals: Option<Vec<Vec3>>,
+        uvs: Option<Vec<[f64; 2]>>,
+        mat: Arc<dyn Material>,
+    ) -> Result<Self> {
+        if indices.is_empty() {
+            bail!(RTError::InvalidMesh("mesh has no triangles".into()));
+        }
+        if let Some(normals) = &normals {
+            if normals.len() != positions.len() {
+                bail!(RTError::InvalidMesh(format!(
+                    "{} normals for {} positions",
+                    normals.len(),
+                    positions.len()
+                )));
+            }
+        }
+        if let Some(uvs) = &uvs {
+            if uvs.len() != positions.len() {
+                bail!(RTError::InvalidMesh(format!(
+                    "{} uvs for {} positions",
+                    uvs.len(),
+                    positions.len()
+                )));
+            }
+        }
+        if let Some((face, _)) = indices
+            .iter()
+            .enumerate()
+            .find(|(_, face)| face.iter().any(|&i| i >= positions.len()))
+        {
+            bail!(RTError::InvalidMesh(format!(
+                "face {} references a vertex out of range",
+                face
+            )));
+        }
+
+        let mesh = Arc::new(MeshData {
+            positions,
+            normals: normals.map(|normals| normals.iter().map(vec3::unit_vector).collect()),
+            uvs,
+            indices,
+            mat,
+        });
+        let mut triangles = HittableList::new();
+
+        for index in 0..mesh.indices.len() {
+            triangles.add(Arc::new(MeshTriangle {
+                mesh: mesh.clone(),
+                index,
+            }));
+        }
+
+        let bvh = LinearBvh::from(&triangles);
+
+        Ok(Self { mesh, bvh })
+    }
+
+    pub fn num_triangles(&self) -> usize {
+        self.mesh.indices.len()
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(&self, r: &Ray, ray_t: &Interval, samples: &mut SampleStream) -> Option<HitRecord<'_>> {
+        self.bvh.hit(r, ray_t, samples)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bvh.bounding_box()
+    }
+}
+
+impl MeshTriangle {
+    fn vertices(&self) -> [&Point3; 3] {
+        let face = &self.mesh.indices[self.index];
+
+        [
+            &self.mesh.positions[face[0]],
+            &self.mesh.positions[face[1]],
+            &self.mesh.positions[face[2]],
+        ]
+    }
+}
+
+impl Hittable for MeshTriangle {
+    fn hit(&self, r: &Ray, ray_t: &Interval, _samples: &mut SampleStream) -> Option<HitRecord<'_>> {
+        let vertices = self.vertices();
+        let [t, b1, b2] = Triangle::intersect(r, ray_t, vertices)?;
+        let b0 = 1_f64 - b1 - b2;
+        let face = &self.mesh.indices[self.index];
+        let geometric_normal = vec3::unit_vector(&vec3::cross(
+            &(*vertices[1] - vertices[0]),
+            &(*vertices[2] - vertices[0]),
+        ));
+        let shading_normal = match &self.mesh.normals {
+            Some(normals) => vec3::unit_vector(
+                &(b0 * normals[face[0]] + b1 * normals[face[1]] + b2 * normals[face[2]]),
+            ),
+            None => geometric_normal,
+        };
+        let [u, v] = match &self.mesh.uvs {
+            Some(uvs) => [
+                b0 * uvs[face[0]][0] + b1 * uvs[face[1]][0] + b2 * uvs[face[2]][0],
+                b0 * uvs[face[0]][1] + b1 * uvs[face[1]][1] + b2 * uvs[face[2]][1],
+            ],
+            None => [b1, b2],
+        };
+        // The side is decided by the true surface; the interpolated normal is
+        // only flipped to agree with it.
+        let front_face = vec3::dot(r.direction(), &geometric_normal) < 0_f64;
+        let outward = if vec3::dot(&shading_normal, &geometric_normal) < 0_f64 {
+            -shading_normal
+        } else {
+            shading_normal
+        };
+
+        Some(HitRecord {
+            p: r.at(t),
+            normal: if front_face { outward } else { -outward },
+            mat: self.mesh.mat.as_ref(),
+            t,
+            u,
+            v,
+            front_face,
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let [a, b, c] = self.vertices();
+
+        Some(Aabb::surrounding_box(
+            &Aabb::new(*a, *b),
+            &Aabb::new(*a, *c),
+        ))
+    }
+}
+
+#[test]
+fn test_triangle_hit_barycentric() {
+    use super::Lambertian;
+    use crate::{
+        sampler::{IndependentSampler, SampleStream},
+        vec3::Color,
+        INFINITY,
+    };
+
+    let sampler = IndependentSampler::new(0);
+    let mut samples = SampleStream::new(&sampler, 0, 0);
+
+    let triangle = Triangle::new(
+        Point3::zeroes(),
+        Point3::new(1_f64, 0_f64, 0_f64),
+        Point3::new(0_f64, 1_f64, 0_f64),
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    );
+    let r = Ray::new(
+        Point3::new(0.25, 0.5, 2_f64),
+        Vec3::new(0_f64, 0_f64, -1_f64),
+        0_f64,
+    );
+    let rec = triangle
+        .hit(&r, &Interval::new(0.001, INFINITY), &mut samples)
+        .expect("ray should hit the triangle");
+    let miss = Ray::new(
+        Point3::new(0.75, 0.5, 2_f64),
+        Vec3::new(0_f64, 0_f64, -1_f64),
+        0_f64,
+    );
+
+    assert_eq!(2_f64, rec.t);
+    assert_eq!(0.25, rec.u);
+    assert_eq!(0.5, rec.v);
+    assert!(rec.front_face);
+    assert!(triangle
+        .hit(&miss, &Interval::new(0.001, INFINITY), &mut samples)
+        .is_none());
+}
+
+#[test]
+fn test_triangle_mesh_interpolates_normals_and_uvs() {
+    use super::Lambertian;
+    use crate::{
+        sampler::{IndependentSampler, SampleStream},
+        vec3::Color,
+        INFINITY,
+    };
+
+    let sampler = IndependentSampler::new(0);
+    let mut samples = SampleStream::new(&sampler, 0, 0);
+
+    let positions = vec![
+        Point3::zeroes(),
+        Point3::new(1_f64, 0_f64, 0_f64),
+        Point3::new(1_f64, 1_f64, 0_f64),
+        Point3::new(0_f64, 1_f64, 0_f64),
+    ];
+    let normals = vec![
+        Vec3::new(-1_f64, 0_f64, 1_f64),
+        Vec3::new(1_f64, 0_f64, 1_f64),
+        Vec3::new(1_f64, 0_f64, 1_f64),
+        Vec3::new(-1_f64, 0_f64, 1_f64),
+    ];
+    let uvs = vec![
+        [0_f64, 0_f64],
+        [1_f64, 0_f64],
+        [1_f64, 1_f64],
+        [0_f64, 1_f64],
+    ];
+    let mesh = TriangleMesh::new(
+        positions,
+        vec![[0, 1, 2], [0, 2, 3]],
+        Some(normals),
+        Some(uvs),
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    )
+    .expect("mesh is valid");
+    let r = Ray::new(
+        Point3::new(0.5, 0.25, 1_f64),
+        Vec3::new(0_f64, 0_f64, -1_f64),
+        0_f64,
+    );
+    let rec = mesh
+        .hit(&r, &Interval::new(0.001, INFINITY), &mut samples)
+        .expect("ray should hit the mesh");
+
+    assert_eq!(2, mesh.num_triangles());
+    assert!((rec.u - 0.5).abs() < 1e-12);
+    assert!((rec.v - 0.25).abs() < 1e-12);
+    assert!((rec.normal - Vec3::new(0_f64, 0_f64, 1_f64)).near_zero());
+}
+
+#[test]
+fn test_triangle_mesh_rejects_out_of_range_indices() {
+    use super::Lambertian;
+    use crate::vec3::Color;
+
+    let mesh = TriangleMesh::new(
+        vec![Point3::zeroes(); 3],
+        vec![[0, 1, 3]],
+        None,
+        None,
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    );
+
+    assert!(mesh.is_err());
+}
@@ -1,4 +1,5 @@
 use crate::INFINITY;
+use std::ops::Add;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Interval {
@@ -64,3 +65,14 @@ impl Default for Interval {
         Self::EMPTY
     }
 }
+
+impl Add<f64> for Interval {
+    type Output = Self;
+
+    fn add(self, displacement: f64) -> Self::Output {
+        Self {
+            min: self.min + displacement,
+            max: self.max + displacement,
+        }
+    }
+}
@@ -1,9 +1,16 @@
+pub mod background;
 pub mod camera;
+pub mod denoise;
 pub mod error;
+pub mod framebuffer;
 pub mod hittable;
 pub mod interval;
+pub mod loader;
 pub mod ray;
+pub mod sampler;
+pub mod scene;
 pub mod texture;
+pub mod tonemap;
 pub mod utils;
 pub mod vec3;
 pub mod writer;
@@ -0,0 +1,29 @@
+mod mtl;
+mod obj;
+
+pub use obj::*;
+
+/// Drops the comment from an OBJ or MTL line. `#` only starts a comment at
+/// the start of a token, so names and paths may contain it.
+fn strip_comment(line: &str) -> &str {
+    let end = line
+        .char_indices()
+        .find(|&(i, c)| {
+            c == '#'
+                && line[..i]
+                    .chars()
+                    .next_back()
+                    .is_none_or(char::is_whitespace)
+        })
+        .map_or(line.len(), |(i, _)| i);
+
+    line[..end].trim()
+}
+
+#[test]
+fn test_strip_comment() {
+    assert_eq!("v 1 2 3", strip_comment("v 1 2 3 # a vertex"));
+    assert_eq!("", strip_comment("# only a comment"));
+    assert_eq!("usemtl glass#2", strip_comment("usemtl glass#2\t#comment"));
+    assert_eq!("mtllib a#b.mtl", strip_comment("  mtllib a#b.mtl  "));
+}
@@ -0,0 +1,197 @@
+use crate::{
+    error::RTError,
+    hittable::{Dielectric, DiffuseLight, Lambertian, Material, Metal},
+    texture::{ImageTexture, Texture},
+    vec3::Color,
+};
+use anyhow::{bail, Context, Result};
+use std::{collections::HashMap, io::BufRead, path::Path, sync::Arc};
+
+#[derive(Debug, Clone)]
+pub(crate) struct MtlMaterial {
+    pub kd: Color,
+    pub ks: Color,
+    pub ke: Color,
+    pub ns: f64,
+    pub ni: f64,
+    pub d: f64,
+    pub illum: i32,
+    pub map_kd: Option<String>,
+}
+
+impl Default for MtlMaterial {
+    fn default() -> Self {
+        Self {
+            kd: Color::new(0.8, 0.8, 0.8),
+            ks: Color::zeroes(),
+            ke: Color::zeroes(),
+            ns: 0_f64,
+            ni: 1_f64,
+            d: 1_f64,
+            illum: 2,
+            map_kd: None,
+        }
+    }
+}
+
+impl MtlMaterial {
+    /// Maps the MTL fields onto the closest material we support: emitters
+    /// first, then transparent, mirror-like and finally diffuse surfaces.
+    pub fn to_material(&self, base_dir: &Path) -> Result<Arc<dyn Material>> {
+        if Self::is_nonzero(&self.ke) {
+            return Ok(Arc::new(DiffuseLight::new(self.ke)));
+        }
+        if self.d < 1_f64 || matches!(self.illum, 4 | 6 | 7 | 9) {
+            let refraction_index = if self.ni > 1_f64 { self.ni } else { 1.5 };
+
+            return Ok(Arc::new(Dielectric::new(refraction_index)));
+        }
+        if self.illum == 3 || (Self::is_nonzero(&self.ks) && !Self::is_nonzero(&self.kd)) {
+            // Phong exponent to a roughness-like fuzz: sharp highlights give little fuzz.
+            let fuzz = (2_f64 / (self.ns + 2_f64)).sqrt().min(1_f64);
+
+            return Ok(Arc::new(Metal::new(self.ks, fuzz)));
+        }
+        if let Some(map_kd) = &self.map_kd {
+            let joined = base_dir.join(map_kd);
+            let tex = ImageTexture::new(joined.to_string_lossy().as_ref())
+                .or_else(|_| ImageTexture::new(map_kd))
+                .with_context(|| format!("failed to load map_Kd {:?}", map_kd))?;
+            let tex: Arc<dyn Texture> = Arc::new(tex);
+
+            return Ok(Arc::new(Lambertian::from(tex)));
+        }
+
+        Ok(Arc::new(Lambertian::new(self.kd)))
+    }
+
+    fn is_nonzero(c: &Color) -> bool {
+        c.x() > 0_f64 || c.y() > 0_f64 || c.z() > 0_f64
+    }
+}
+
+pub(crate) fn parse_mtl<R: BufRead>(reader: R) -> Result<HashMap<String, MtlMaterial>> {
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, MtlMaterial)> = None;
+
+    for (index, line) in reader.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line?;
+        let line = super::strip_comment(&line);
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let args = tokens.collect::<Vec<&str>>();
+
+        if keyword == "newmtl" {
+            if let Some((name, mat)) = current.take() {
+                materials.insert(name, mat);
+            }
+            if args.is_empty() {
+                bail!(RTError::MtlParse(
+                    line_number,
+                    "newmtl without a name".into()
+                ));
+            }
+
+            current = Some((args.join(" "), MtlMaterial::default()));
+            continue;
+        }
+
+        let mat = match current.as_mut() {
+            Some((_, mat)) => mat,
+            None => bail!(RTError::MtlParse(
+                line_number,
+                format!("{} before any newmtl", keyword)
+            )),
+        };
+
+        match keyword {
+            "Kd" => mat.kd = parse_color(&args, line_number)?,
+            "Ks" => mat.ks = parse_color(&args, line_number)?,
+            "Ke" => mat.ke = parse_color(&args, line_number)?,
+            "Ns" => mat.ns = parse_scalar(&args, line_number)?,
+            "Ni" => mat.ni = parse_scalar(&args, line_number)?,
+            "d" => mat.d = parse_scalar(&args, line_number)?,
+            "Tr" => mat.d = 1_f64 - parse_scalar(&args, line_number)?,
+            "illum" => mat.illum = parse_scalar(&args, line_number)? as i32,
+            "map_Kd" => match args.last() {
+                // Texture options (-s, -o, ...) come first; the file name is last.
+                Some(file) => mat.map_kd = Some(file.to_string()),
+                None => bail!(RTError::MtlParse(
+                    line_number,
+                    "map_Kd without a file name".into()
+                )),
+            },
+            _ => {}
+        }
+    }
+
+    if let Some((name, mat)) = current {
+        materials.insert(name, mat);
+    }
+
+    Ok(materials)
+}
+
+fn parse_scalar(args: &[&str], line_number: usize) -> Result<f64> {
+    match args.first().map(|arg| arg.parse::<f64>()) {
+        Some(Ok(value)) => Ok(value),
+        _ => bail!(RTError::MtlParse(
+            line_number,
+            format!("expected a number, found {:?}", args.join(" "))
+        )),
+    }
+}
+
+fn parse_color(args: &[&str], line_number: usize) -> Result<Color> {
+    let values = args
+        .iter()
+        .map(|arg| arg.parse::<f64>())
+        .collect::<std::result::Result<Vec<f64>, _>>();
+
+    match values.as_deref() {
+        Ok([r, g, b]) => Ok(Color::new(*r, *g, *b)),
+        Ok([v]) => Ok(Color::new(*v, *v, *v)),
+        _ => bail!(RTError::MtlParse(
+            line_number,
+            format!("expected an RGB color, found {:?}", args.join(" "))
+        )),
+    }
+}
+
+#[test]
+fn test_parse_mtl_fields() {
+    let source = "\
+# two materials
+newmtl glass
+Kd 0 0 0
+Ni 1.45
+d 0.2
+
+newmtl lamp
+Ke 4 4 4
+map_Kd -s 1 1 1 textures/lamp.png
+";
+    let materials = parse_mtl(source.as_bytes()).expect("valid mtl");
+    let glass = &materials["glass"];
+    let lamp = &materials["lamp"];
+
+    assert_eq!(1.45, glass.ni);
+    assert_eq!(0.2, glass.d);
+    assert_eq!(Color::new(4_f64, 4_f64, 4_f64), lamp.ke);
+    assert_eq!(Some("textures/lamp.png".to_string()), lamp.map_kd);
+}
+
+#[test]
+fn test_parse_mtl_reports_line_number() {
+    let source = "newmtl a\nKd 1 x 1\n";
+    let err = parse_mtl(source.as_bytes()).expect_err("Kd is malformed");
+
+    match err.downcast_ref::<RTError>() {
+        Some(RTError::MtlParse(line, _)) => assert_eq!(2, *line),
+        _ => panic!("unexpected error: {}", err),
+    }
+}
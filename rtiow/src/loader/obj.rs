@@ -0,0 +1,395 @@
+use super::mtl::{self, MtlMaterial};
+use crate::{
+    error::RTError,
+    hittable::{HittableList, Material, TriangleMesh},
+    vec3::{Point3, Vec3},
+};
+use anyhow::{bail, Context, Result};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+    sync::Arc,
+};
+
+/// Loads a Wavefront OBJ file (and the MTL libraries it references) into one
+/// `TriangleMesh` per group and material. Faces with and without normals or
+/// texture coordinates go into separate meshes, so each face keeps the
+/// attributes it has.
+pub fn load_obj(path: &str) -> Result<HittableList> {
+    let path = Path::new(path);
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    let reader = BufReader::new(File::open(path)?);
+
+    parse_obj(reader, base_dir)
+}
+
+#[derive(Debug, Default)]
+struct MeshBuilder {
+    positions: Vec<Point3>,
+    normals: Vec<Option<Vec3>>,
+    uvs: Vec<Option<[f64; 2]>>,
+    indices: Vec<[usize; 3]>,
+    lookup: HashMap<[Option<usize>; 3], usize>,
+}
+
+impl MeshBuilder {
+    fn vertex(&mut self, key: [Option<usize>; 3], obj: &ObjData) -> usize {
+        if let Some(&index) = self.lookup.get(&key) {
+            return index;
+        }
+
+        let index = self.positions.len();
+
+        self.positions
+            .push(obj.positions[key[0].expect("positions are always indexed")]);
+        self.uvs.push(key[1].map(|vt| obj.uvs[vt]));
+        self.normals.push(key[2].map(|vn| obj.normals[vn]));
+        self.lookup.insert(key, index);
+
+        index
+    }
+
+    fn build(self, mat: Arc<dyn Material>) -> Result<TriangleMesh> {
+        let normals = self.normals.into_iter().collect::<Option<Vec<Vec3>>>();
+        let uvs = self.uvs.into_iter().collect::<Option<Vec<[f64; 2]>>>();
+
+        TriangleMesh::new(self.positions, self.indices, normals, uvs, mat)
+    }
+}
+
+/// Group, material and whether the faces have texture coordinates and
+/// normals.
+type MeshKey = (String, Option<String>, [bool; 2]);
+
+#[derive(Debug, Default)]
+struct ObjData {
+    positions: Vec<Point3>,
+    uvs: Vec<[f64; 2]>,
+    normals: Vec<Vec3>,
+}
+
+pub(crate) fn parse_obj<R: BufRead>(reader: R, base_dir: &Path) -> Result<HittableList> {
+    let mut obj = ObjData::default();
+    let mut library: HashMap<String, MtlMaterial> = HashMap::new();
+    let mut group = String::from("default");
+    let mut usemtl: Option<String> = None;
+    let mut meshes: Vec<(MeshKey, MeshBuilder)> = Vec::new();
+    let mut line_count = 0;
+
+    for (index, line) in reader.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line?;
+        let line = super::strip_comment(&line);
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let args = tokens.collect::<Vec<&str>>();
+
+        line_count = line_number;
+
+        match keyword {
+            "v" => {
+                let [x, y, z] = parse_floats::<3>(&args, line_number)?;
+
+                obj.positions.push(Point3::new(x, y, z));
+            }
+            "vt" => {
+                // `v` and `w` are optional; `w` is ignored.
+                let uv = if args.len() < 2 {
+                    [parse_floats::<1>(&args, line_number)?[0], 0_f64]
+                } else {
+                    parse_floats::<2>(&args, line_number)?
+                };
+
+                obj.uvs.push(uv);
+            }
+            "vn" => {
+                let [x, y, z] = parse_floats::<3>(&args, line_number)?;
+
+                obj.normals.push(Vec3::new(x, y, z));
+            }
+            "f" => {
+                if args.len() < 3 {
+                    bail!(RTError::ObjParse(
+                        line_number,
+                        format!("face needs at least 3 vertices, found {}", args.len())
+                    ));
+                }
+
+                let keys = args
+                    .iter()
+                    .map(|arg| parse_face_vertex(arg, &obj, line_number))
+                    .collect::<Result<Vec<_>>>()?;
+                let attributes = [1, 2].map(|i| keys.iter().all(|key| key[i].is_some()));
+
+                for (i, attribute) in [(1, "texture coordinates"), (2, "normals")] {
+                    if !attributes[i - 1] && keys.iter().any(|key| key[i].is_some()) {
+                        bail!(RTError::ObjParse(
+                            line_number,
+                            format!("only some vertices of the face have {}", attribute)
+                        ));
+                    }
+                }
+
+                let mesh_key = (group.clone(), usemtl.clone(), attributes);
+                let mesh = match meshes.iter().position(|(key, _)| *key == mesh_key) {
+                    Some(i) => &mut meshes[i].1,
+                    None => {
+                        meshes.push((mesh_key, MeshBuilder::default()));
+                        &mut meshes.last_mut().expect("mesh was just pushed").1
+                    }
+                };
+                let fan = keys
+                    .iter()
+                    .map(|key| mesh.vertex(*key, &obj))
+                    .collect::<Vec<usize>>();
+
+                // Polygons are triangulated as a fan around their first vertex.
+                for i in 1..(fan.len() - 1) {
+                    mesh.indices.push([fan[0], fan[i], fan[i + 1]]);
+                }
+            }
+            "g" | "o" => {
+                group = if args.is_empty() {
+                    String::from("default")
+                } else {
+                    args.join(" ")
+                };
+            }
+            "usemtl" => {
+                let name = args.join(" ");
+
+                if !library.contains_key(&name) {
+                    bail!(RTError::ObjParse(
+                        line_number,
+                        format!("unknown material {:?}", name)
+                    ));
+                }
+
+                usemtl = Some(name);
+            }
+            "mtllib" => {
+                for file in args.iter() {
+                    let reader = match File::open(base_dir.join(file)) {
+                        Ok(file) => BufReader::new(file),
+                        Err(e) => bail!(RTError::ObjParse(
+                            line_number,
+                            format!("cannot open mtllib {:?}: {}", file, e)
+                        )),
+                    };
+
+                    library.extend(
+                        mtl::parse_mtl(reader).with_context(|| format!("in mtllib {:?}", file))?,
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if meshes.is_empty() {
+        bail!(RTError::ObjParse(
+            line_count,
+            "file contains no faces".into()
+        ));
+    }
+
+    let mut materials: HashMap<Option<String>, Arc<dyn Material>> = HashMap::new();
+    let mut world = HittableList::new();
+
+    for ((_, name, _), mesh) in meshes {
+        let mat = match materials.get(&name) {
+            Some(mat) => mat.clone(),
+            None => {
+                let mat = match &name {
+                    Some(name) => library[name].to_material(base_dir)?,
+                    None => MtlMaterial::default().to_material(base_dir)?,
+                };
+
+                materials.insert(name, mat.clone());
+
+                mat
+            }
+        };
+
+        world.add(Arc::new(mesh.build(mat)?));
+    }
+
+    Ok(world)
+}
+
+fn parse_floats<const N: usize>(args: &[&str], line_number: usize) -> Result<[f64; N]> {
+    let mut ret = [0_f64; N];
+
+    if args.len() < N {
+        bail!(RTError::ObjParse(
+            line_number,
+            format!("expected {} numbers, found {:?}", N, args.join(" "))
+        ));
+    }
+
+    for (value, arg) in ret.iter_mut().zip(args.iter()) {
+        *value = match arg.parse::<f64>() {
+            Ok(value) => value,
+            Err(_) => bail!(RTError::ObjParse(
+                line_number,
+                format!("invalid number {:?}", arg)
+            )),
+        };
+    }
+
+    Ok(ret)
+}
+
+fn parse_face_vertex(arg: &str, obj: &ObjData, line_number: usize) -> Result<[Option<usize>; 3]> {
+    let mut parts = arg.split('/');
+    let counts = [obj.positions.len(), obj.uvs.len(), obj.normals.len()];
+    let mut key = [None; 3];
+
+    for (i, slot) in key.iter_mut().enumerate() {
+        let part = match parts.next() {
+            Some(part) if !part.is_empty() => part,
+            _ if i == 0 => bail!(RTError::ObjParse(
+                line_number,
+                format!("face vertex {:?} has no position", arg)
+            )),
+            _ => continue,
+        };
+        let index = match part.parse::<i64>() {
+            Ok(index) => index,
+            Err(_) => bail!(RTError::ObjParse(
+                line_number,
+                format!("invalid index {:?} in {:?}", part, arg)
+            )),
+        };
+        // OBJ indices are 1-based; negative ones count back from the latest element.
+        let resolved = if index > 0 {
+            index - 1
+        } else {
+            counts[i] as i64 + index
+        };
+
+        if index == 0 || resolved < 0 || resolved >= counts[i] as i64 {
+            bail!(RTError::ObjParse(
+                line_number,
+                format!("index {} out of range in {:?}", index, arg)
+            ));
+        }
+
+        *slot = Some(resolved as usize);
+    }
+
+    Ok(key)
+}
+
+#[test]
+fn test_parse_obj_triangulates_polygons() {
+    use crate::{
+        hittable::Hittable,
+        interval::Interval,
+        ray::Ray,
+        sampler::{IndependentSampler, SampleStream},
+        INFINITY,
+    };
+
+    let sampler = IndependentSampler::new(0);
+    let mut samples = SampleStream::new(&sampler, 0, 0);
+
+    let source = "\
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+g quad
+f 1/1 2/2 3/3 4/4
+g tri
+f -4 -3 -1
+";
+    let world = parse_obj(source.as_bytes(), Path::new("")).expect("valid obj");
+    let r = Ray::new(
+        Point3::new(0.75, 0.5, 1_f64),
+        Vec3::new(0_f64, 0_f64, -1_f64),
+        0_f64,
+    );
+    let rec = world
+        .hit(&r, &Interval::new(0.001, INFINITY), &mut samples)
+        .expect("ray should hit the quad");
+
+    assert_eq!(2, world.objects.len());
+    assert!((rec.u - 0.75).abs() < 1e-12);
+    assert!((rec.v - 0.5).abs() < 1e-12);
+}
+
+#[test]
+fn test_parse_obj_reports_line_number() {
+    let source = "v 0 0 0\nv 1 0 0\n\nf 1 2 3\n";
+    let err = parse_obj(source.as_bytes(), Path::new("")).expect_err("index 3 is undefined");
+
+    match err.downcast_ref::<RTError>() {
+        Some(RTError::ObjParse(line, _)) => assert_eq!(4, *line),
+        _ => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+fn test_parse_obj_keeps_attributes_per_face() {
+    let source = "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vt 0.5
+vt 0.25 0.75 0
+vn 0 0 1
+f 1/1/1 2/2/1 3/2/1
+f 1 2 3
+f 1//1 2//1 3//1
+";
+    let world = parse_obj(source.as_bytes(), Path::new("")).expect("valid obj");
+
+    // One mesh each for faces with both attributes, neither and normals only
+    assert_eq!(3, world.objects.len());
+
+    let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 1\nf 1//1 2 3//1\n";
+    let err = parse_obj(source.as_bytes(), Path::new("")).expect_err("face mixes normals");
+
+    match err.downcast_ref::<RTError>() {
+        Some(RTError::ObjParse(line, message)) => {
+            assert_eq!(5, *line);
+            assert!(message.contains("normals"), "{}", message);
+        }
+        _ => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+fn test_load_obj_with_mtllib() {
+    use std::{env, fs};
+
+    let dir = env::temp_dir().join(format!("rtiow_obj_{}", std::process::id()));
+
+    fs::create_dir_all(&dir).expect("temp dir is writable");
+    fs::write(
+        dir.join("scene#1.mtl"),
+        "newmtl light\nKe 5 5 5\nnewmtl mirror#2 # polished\nKd 0 0 0\nKs 0.9 0.9 0.9\nNs 1000\n",
+    )
+    .expect("temp dir is writable");
+    fs::write(
+        dir.join("scene.obj"),
+        "mtllib scene#1.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl light\nf 1 2 3\nusemtl mirror#2\nf 3 2 1\n",
+    )
+    .expect("temp dir is writable");
+
+    let world = load_obj(dir.join("scene.obj").to_string_lossy().as_ref()).expect("valid obj");
+
+    fs::remove_dir_all(&dir).ok();
+
+    assert_eq!(2, world.objects.len());
+}
@@ -0,0 +1,83 @@
+use super::{IndependentSampler, Sampler, ONE_MINUS_EPSILON};
+use crate::utils::Rng;
+
+/// The Halton sequence, with dimension `d` the radical inverse of the sample
+/// index in the `d`-th prime base. Every pixel shifts each dimension by its
+/// own random offset modulo one (Cranley-Patterson rotation), so pixels do
+/// not share sample positions.
+///
+/// High prime bases correlate badly, so dimensions past `PRIMES` fall back
+/// to independent values.
+#[derive(Debug, Clone, Copy)]
+pub struct HaltonSampler {
+    seed: u64,
+    fallback: IndependentSampler,
+}
+
+impl HaltonSampler {
+    const PRIMES: [u64; 32] = [
+        2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89,
+        97, 101, 103, 107, 109, 113, 127, 131,
+    ];
+
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            fallback: IndependentSampler::new(seed),
+        }
+    }
+
+    fn rotation(&self, pixel: u64, dimension: u32) -> f64 {
+        Rng::hashed(&[self.seed, pixel, dimension as u64]).random()
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn get_1d(&self, pixel: u64, index: u32, dimension: u32) -> f64 {
+        match Self::PRIMES.get(dimension as usize) {
+            Some(base) => {
+                let value = radical_inverse(*base, index as u64) + self.rotation(pixel, dimension);
+
+                // `fract` of a value just below an integer can round to one
+                (value - value.floor()).min(ONE_MINUS_EPSILON)
+            }
+            None => self.fallback.get_1d(pixel, index, dimension),
+        }
+    }
+
+    fn get_2d(&self, pixel: u64, index: u32, dimension: u32) -> [f64; 2] {
+        [
+            self.get_1d(pixel, index, dimension),
+            self.get_1d(pixel, index, dimension + 1),
+        ]
+    }
+}
+
+/// Mirrors the digits of `a` in `base` about the radix point.
+fn radical_inverse(base: u64, mut a: u64) -> f64 {
+    let inv_base = 1_f64 / base as f64;
+    let mut reversed_digits = 0_u64;
+    let mut inv_base_n = 1_f64;
+
+    while a > 0 {
+        let next = a / base;
+        let digit = a - next * base;
+
+        reversed_digits = reversed_digits * base + digit;
+        inv_base_n *= inv_base;
+        a = next;
+    }
+
+    reversed_digits as f64 * inv_base_n
+}
+
+#[test]
+fn test_radical_inverse() {
+    assert_eq!(
+        vec![0_f64, 0.5, 0.25, 0.75, 0.125],
+        (0..5).map(|a| radical_inverse(2, a)).collect::<Vec<_>>()
+    );
+    for (a, expected) in [(1, 1_f64 / 3_f64), (2, 2_f64 / 3_f64), (3, 1_f64 / 9_f64)] {
+        assert!((radical_inverse(3, a) - expected).abs() < 1e-15);
+    }
+}
@@ -0,0 +1,31 @@
+use super::Sampler;
+use crate::utils::Rng;
+
+/// Independent uniform random values, hashed from the seed, pixel, sample
+/// index and dimension.
+#[derive(Debug, Clone, Copy)]
+pub struct IndependentSampler {
+    seed: u64,
+}
+
+impl IndependentSampler {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    fn rng(&self, pixel: u64, index: u32, dimension: u32) -> Rng {
+        Rng::hashed(&[self.seed, pixel, index as u64, dimension as u64])
+    }
+}
+
+impl Sampler for IndependentSampler {
+    fn get_1d(&self, pixel: u64, index: u32, dimension: u32) -> f64 {
+        self.rng(pixel, index, dimension).random()
+    }
+
+    fn get_2d(&self, pixel: u64, index: u32, dimension: u32) -> [f64; 2] {
+        let mut rng = self.rng(pixel, index, dimension);
+
+        [rng.random(), rng.random()]
+    }
+}
@@ -0,0 +1,155 @@
+use serde::Deserialize;
+use std::{fmt::Debug, sync::Arc};
+
+mod halton;
+mod independent;
+mod sobol;
+mod stratified;
+
+pub use halton::*;
+pub use independent::*;
+pub use sobol::*;
+pub use stratified::*;
+
+/// Source of the sample values that drive one camera sample: pixel jitter,
+/// lens position, time and every scattering decision along the path.
+///
+/// Samplers are stateless. A value is identified by the pixel, the index of
+/// the sample within the pixel and its dimension, and must lie in `[0, 1)`.
+/// `SampleStream` hands out the dimensions of one sample in order.
+pub trait Sampler: Debug + Send + Sync {
+    fn get_1d(&self, pixel: u64, index: u32, dimension: u32) -> f64;
+
+    /// Two values meant to be used together, occupying `dimension` and
+    /// `dimension + 1`.
+    fn get_2d(&self, pixel: u64, index: u32, dimension: u32) -> [f64; 2];
+}
+
+/// Largest `f64` below one, for clamping values that round up to one.
+const ONE_MINUS_EPSILON: f64 = 1_f64 - f64::EPSILON / 2_f64;
+
+/// The sample values of one camera sample, drawn dimension after dimension.
+/// The camera and the materials draw them in a fixed order, so the same
+/// dimension always drives the same decision.
+#[derive(Debug)]
+pub struct SampleStream<'a> {
+    sampler: &'a dyn Sampler,
+    pixel: u64,
+    index: u32,
+    dimension: u32,
+}
+
+/// Samplers that can be selected by name, e.g. in scene files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SamplerKind {
+    #[default]
+    Independent,
+    Stratified,
+    Halton,
+    Sobol,
+}
+
+impl<'a> SampleStream<'a> {
+    pub fn new(sampler: &'a dyn Sampler, pixel: u64, index: u32) -> Self {
+        Self {
+            sampler,
+            pixel,
+            index,
+            dimension: 0,
+        }
+    }
+
+    pub fn next_1d(&mut self) -> f64 {
+        let value = self.sampler.get_1d(self.pixel, self.index, self.dimension);
+
+        self.dimension += 1;
+        value
+    }
+
+    pub fn next_2d(&mut self) -> [f64; 2] {
+        let value = self.sampler.get_2d(self.pixel, self.index, self.dimension);
+
+        self.dimension += 2;
+        value
+    }
+}
+
+impl SamplerKind {
+    pub fn build(&self, samples_per_pixel: u32, seed: u64) -> Arc<dyn Sampler> {
+        match self {
+            Self::Independent => Arc::new(IndependentSampler::new(seed)),
+            Self::Stratified => Arc::new(StratifiedSampler::new(samples_per_pixel, seed)),
+            Self::Halton => Arc::new(HaltonSampler::new(seed)),
+            Self::Sobol => Arc::new(SobolSampler::new(seed)),
+        }
+    }
+}
+
+#[test]
+fn test_samplers_converge_faster_than_independent() {
+    // Estimates the area of a quarter disk in many pixels and compares the
+    // mean error of each sampler
+    let error = |kind: SamplerKind| {
+        let sampler = kind.build(64, 3);
+
+        (0..256_u64)
+            .map(|pixel| {
+                let inside = (0..64)
+                    .filter(|index| {
+                        let mut stream = SampleStream::new(sampler.as_ref(), pixel, *index);
+                        let [x, y] = stream.next_2d();
+
+                        x * x + y * y < 1_f64
+                    })
+                    .count();
+
+                (inside as f64 / 64_f64 - crate::PI / 4_f64).abs()
+            })
+            .sum::<f64>()
+            / 256_f64
+    };
+    let independent = error(SamplerKind::Independent);
+
+    for kind in [
+        SamplerKind::Stratified,
+        SamplerKind::Halton,
+        SamplerKind::Sobol,
+    ] {
+        let error = error(kind);
+
+        assert!(
+            error < 0.6 * independent,
+            "{:?}: {} vs {}",
+            kind,
+            error,
+            independent
+        );
+    }
+}
+
+#[test]
+fn test_samplers_stay_in_unit_interval() {
+    for kind in [
+        SamplerKind::Independent,
+        SamplerKind::Stratified,
+        SamplerKind::Halton,
+        SamplerKind::Sobol,
+    ] {
+        let sampler = kind.build(9, 1);
+
+        for index in 0..12 {
+            let mut stream = SampleStream::new(sampler.as_ref(), 5, index);
+            let mut again = SampleStream::new(sampler.as_ref(), 5, index);
+
+            for _ in 0..40 {
+                let [x, y] = stream.next_2d();
+                let z = stream.next_1d();
+
+                assert!([x, y, z].iter().all(|v| (0_f64..1_f64).contains(v)));
+                assert_eq!([x, y], again.next_2d());
+                assert_eq!(z, again.next_1d());
+            }
+        }
+    }
+}
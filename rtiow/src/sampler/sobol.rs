@@ -0,0 +1,115 @@
+use super::{Sampler, ONE_MINUS_EPSILON};
+use crate::utils;
+
+/// Owen-scrambled Sobol' points, padded to any number of dimensions: every
+/// pair of dimensions takes the first two Sobol' dimensions, with the sample
+/// index shuffled and the values scrambled by hashes of the pixel and the
+/// dimension (Burley, "Practical Hash-based Owen Scrambling", 2020).
+///
+/// For power-of-two sample counts the samples of a pixel are stratified in
+/// every 1D and 2D projection.
+#[derive(Debug, Clone, Copy)]
+pub struct SobolSampler {
+    seed: u64,
+}
+
+impl SobolSampler {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    /// Scrambling seeds of `dimension`: one to shuffle the sample index and
+    /// one per value.
+    fn seeds(&self, pixel: u64, dimension: u32) -> [u32; 3] {
+        let hash = utils::hash(&[self.seed, pixel, dimension as u64]);
+
+        [
+            hash as u32,
+            (hash >> 32) as u32,
+            utils::hash(&[hash]) as u32,
+        ]
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn get_1d(&self, pixel: u64, index: u32, dimension: u32) -> f64 {
+        let [shuffle, scramble, _] = self.seeds(pixel, dimension);
+        let index = nested_uniform_scramble(index, shuffle);
+
+        to_unit(nested_uniform_scramble(index.reverse_bits(), scramble))
+    }
+
+    fn get_2d(&self, pixel: u64, index: u32, dimension: u32) -> [f64; 2] {
+        let [shuffle, scramble_x, scramble_y] = self.seeds(pixel, dimension);
+        let index = nested_uniform_scramble(index, shuffle);
+
+        [
+            to_unit(nested_uniform_scramble(index.reverse_bits(), scramble_x)),
+            to_unit(nested_uniform_scramble(sobol_second(index), scramble_y)),
+        ]
+    }
+}
+
+/// Second dimension of the Sobol' sequence; the first is the bit-reversed
+/// index.
+fn sobol_second(mut index: u32) -> u32 {
+    let mut value = 0_u32;
+    let mut direction = 1_u32 << 31;
+
+    while index != 0 {
+        if index & 1 == 1 {
+            value ^= direction;
+        }
+        index >>= 1;
+        direction ^= direction >> 1;
+    }
+
+    value
+}
+
+/// Owen scrambling of the bits of `x`, most significant first.
+fn nested_uniform_scramble(x: u32, seed: u32) -> u32 {
+    laine_karras_permutation(x.reverse_bits(), seed).reverse_bits()
+}
+
+/// Hash whose low bits only depend on lower bits of `x`, which scrambles
+/// bit-reversed values like an Owen scramble.
+fn laine_karras_permutation(mut x: u32, seed: u32) -> u32 {
+    x = x.wrapping_add(seed);
+    x ^= x.wrapping_mul(0x6c50_b47c);
+    x ^= x.wrapping_mul(0xb82f_1e52);
+    x ^= x.wrapping_mul(0xc7af_e638);
+    x ^= x.wrapping_mul(0x8d22_f6e6);
+
+    x
+}
+
+fn to_unit(x: u32) -> f64 {
+    (x as f64 / (1_u64 << 32) as f64).min(ONE_MINUS_EPSILON)
+}
+
+#[test]
+fn test_sobol_sampler_is_a_net() {
+    // 16 points fill every elementary interval of area 1/16 once
+    let sampler = SobolSampler::new(4);
+
+    for dimension in [0, 2, 6] {
+        let points = (0..16)
+            .map(|index| sampler.get_2d(7, index, dimension))
+            .collect::<Vec<[f64; 2]>>();
+
+        for log_x in 0..=4 {
+            let (nx, ny) = (1_u32 << log_x, 1_u32 << (4 - log_x));
+            let mut cells = points
+                .iter()
+                .map(|[x, y]| (y * ny as f64) as u32 * nx + (x * nx as f64) as u32)
+                .collect::<Vec<u32>>();
+
+            cells.sort();
+
+            assert_eq!((0..16).collect::<Vec<u32>>(), cells, "{}x{}", nx, ny);
+        }
+    }
+
+    assert_ne!(sampler.get_2d(7, 0, 0), sampler.get_2d(8, 0, 0));
+}
@@ -0,0 +1,138 @@
+use super::{IndependentSampler, Sampler, ONE_MINUS_EPSILON};
+use crate::utils::{self, Rng};
+
+/// Jittered stratified sampling as in The Rest of Your Life: 2D dimensions
+/// place one sample in each cell of a `sqrt_spp` x `sqrt_spp` grid and 1D
+/// dimensions one in each of `spp` intervals. Every dimension visits its
+/// strata in its own pseudo-random order, so dimensions are not correlated.
+///
+/// Samples past the last full grid, which only exist when the sample count
+/// is not a perfect square, fall back to independent values.
+#[derive(Debug, Clone, Copy)]
+pub struct StratifiedSampler {
+    samples_per_pixel: u32,
+    sqrt_spp: u32,
+    seed: u64,
+    fallback: IndependentSampler,
+}
+
+impl StratifiedSampler {
+    pub fn new(samples_per_pixel: u32, seed: u64) -> Self {
+        let samples_per_pixel = samples_per_pixel.max(1);
+
+        Self {
+            samples_per_pixel,
+            sqrt_spp: (samples_per_pixel as f64).sqrt() as u32,
+            seed,
+            fallback: IndependentSampler::new(seed),
+        }
+    }
+
+    /// The stratum sample `index` falls into among `count` strata, and the
+    /// generator for its jitter.
+    fn stratum(&self, pixel: u64, index: u32, dimension: u32, count: u32) -> (u32, Rng) {
+        let permutation = utils::hash(&[self.seed, pixel, dimension as u64]) as u32;
+
+        (
+            permutation_element(index, count, permutation),
+            Rng::hashed(&[self.seed, pixel, index as u64, dimension as u64]),
+        )
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn get_1d(&self, pixel: u64, index: u32, dimension: u32) -> f64 {
+        if index >= self.samples_per_pixel {
+            return self.fallback.get_1d(pixel, index, dimension);
+        }
+
+        let (stratum, mut rng) = self.stratum(pixel, index, dimension, self.samples_per_pixel);
+
+        ((stratum as f64 + rng.random()) / self.samples_per_pixel as f64).min(ONE_MINUS_EPSILON)
+    }
+
+    fn get_2d(&self, pixel: u64, index: u32, dimension: u32) -> [f64; 2] {
+        let n = self.sqrt_spp;
+
+        if index >= n * n {
+            return self.fallback.get_2d(pixel, index, dimension);
+        }
+
+        let (stratum, mut rng) = self.stratum(pixel, index, dimension, n * n);
+        let x = (stratum % n) as f64 + rng.random();
+        let y = (stratum / n) as f64 + rng.random();
+
+        [
+            (x / n as f64).min(ONE_MINUS_EPSILON),
+            (y / n as f64).min(ONE_MINUS_EPSILON),
+        ]
+    }
+}
+
+/// Element `i` of a pseudo-random permutation of `0..l` chosen by `p`,
+/// computed without storing the permutation (Kensler, "Correlated
+/// Multi-Jittered Sampling", 2013).
+fn permutation_element(mut i: u32, l: u32, p: u32) -> u32 {
+    let mut w = l - 1;
+
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+
+    // Cycle-walk until the permuted value lands inside `0..l`
+    loop {
+        i ^= p;
+        i = i.wrapping_mul(0xe170_893d);
+        i ^= p >> 16;
+        i ^= (i & w) >> 4;
+        i ^= p >> 8;
+        i = i.wrapping_mul(0x0929_eb3f);
+        i ^= p >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | p >> 27);
+        i = i.wrapping_mul(0x6935_fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dc_b303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e50_1cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860_a3df);
+        i &= w;
+        i ^= i >> 5;
+
+        if i < l {
+            break;
+        }
+    }
+
+    i.wrapping_add(p) % l
+}
+
+#[test]
+fn test_stratified_sampler_fills_every_stratum() {
+    let sampler = StratifiedSampler::new(16, 9);
+
+    for dimension in [0, 2, 4] {
+        let mut cells = (0..16)
+            .map(|index| {
+                let [x, y] = sampler.get_2d(3, index, dimension);
+
+                (y * 4_f64) as u32 * 4 + (x * 4_f64) as u32
+            })
+            .collect::<Vec<u32>>();
+        let mut intervals = (0..16)
+            .map(|index| (sampler.get_1d(3, index, dimension) * 16_f64) as u32)
+            .collect::<Vec<u32>>();
+
+        cells.sort();
+        intervals.sort();
+
+        assert_eq!((0..16).collect::<Vec<u32>>(), cells);
+        assert_eq!((0..16).collect::<Vec<u32>>(), intervals);
+    }
+
+    // Dimensions visit the strata in different orders
+    assert_ne!(sampler.get_2d(3, 0, 0), sampler.get_2d(3, 0, 2));
+}
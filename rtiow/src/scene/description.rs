@@ -0,0 +1,460 @@
+use super::Scene;
+use crate::{
+    background::{Background, EnvironmentMap, GradientBackground, SolidBackground},
+    camera::CameraBuilder,
+    error::RTError,
+    hittable::{
+        self, ConstantMedium, Dielectric, DiffuseLight, Hittable, HittableList, Isotropic,
+        Lambertian, LinearBvh, Material, Metal, Quad, RotateY, Sphere, Translate, Triangle,
+    },
+    loader,
+    texture::{CheckerTexture, ColorSpace, ImageTexture, NoiseTexture, SolidColor, Texture},
+    utils::Rng,
+    vec3::{self, Color, Point3, Vec3},
+};
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+    sync::Arc,
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct SceneDescription {
+    #[serde(default)]
+    camera: CameraBuilder,
+    background: Option<BackgroundDescription>,
+    #[serde(default)]
+    textures: BTreeMap<String, TextureDescription>,
+    #[serde(default)]
+    materials: BTreeMap<String, MaterialDescription>,
+    #[serde(default)]
+    objects: Vec<ObjectDescription>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum BackgroundDescription {
+    Solid { color: Color },
+    Gradient { bottom: Color, top: Color },
+    Environment { file: String },
+}
+
+/// Either an inline RGB color or the name of a texture.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum TextureReference {
+    Color(Color),
+    Named(String),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum TextureDescription {
+    Solid {
+        color: Color,
+    },
+    Checker {
+        scale: f64,
+        even: TextureReference,
+        odd: TextureReference,
+    },
+    Image {
+        file: String,
+        /// `linear` keeps data textures such as normal maps undecoded.
+        #[serde(default)]
+        color_space: ColorSpace,
+    },
+    /// Perlin noise whose gradient table is drawn from `seed`, so a scene
+    /// file always renders the same noise.
+    Noise {
+        scale: f64,
+        #[serde(default)]
+        seed: u64,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum MaterialDescription {
+    Lambertian {
+        albedo: TextureReference,
+    },
+    Metal {
+        albedo: Color,
+        #[serde(default)]
+        fuzz: f64,
+    },
+    Dielectric {
+        refraction_index: f64,
+    },
+    DiffuseLight {
+        emit: TextureReference,
+    },
+    Isotropic {
+        albedo: TextureReference,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ObjectDescription {
+    name: Option<String>,
+    material: Option<String>,
+    translate: Option<Vec3>,
+    rotate_y: Option<f64>,
+    shape: ShapeDescription,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum ShapeDescription {
+    Sphere {
+        center: Point3,
+        center2: Option<Point3>,
+        radius: f64,
+    },
+    Quad {
+        q: Point3,
+        u: Vec3,
+        v: Vec3,
+    },
+    Box {
+        a: Point3,
+        b: Point3,
+    },
+    Triangle {
+        a: Point3,
+        b: Point3,
+        c: Point3,
+    },
+    Obj {
+        file: String,
+    },
+    ConstantMedium {
+        boundary: Box<ObjectDescription>,
+        density: f64,
+        albedo: TextureReference,
+    },
+}
+
+/// Resolves named textures and materials on first use, so entries may refer
+/// to each other in any order.
+struct Resolver<'a> {
+    description: &'a SceneDescription,
+    base_dir: &'a Path,
+    textures: HashMap<String, Arc<dyn Texture>>,
+    materials: HashMap<String, Arc<dyn Material>>,
+    pending: Vec<String>,
+}
+
+impl SceneDescription {
+    pub fn build(&self, base_dir: &Path) -> Result<Scene> {
+        let mut resolver = Resolver {
+            description: self,
+            base_dir,
+            textures: HashMap::new(),
+            materials: HashMap::new(),
+            pending: Vec::new(),
+        };
+        let mut objects = HittableList::new();
+
+        // Resolve every entry, not only the referenced ones, so typos in
+        // unused definitions are still reported.
+        for name in self.textures.keys() {
+            resolver.texture(name)?;
+        }
+        for name in self.materials.keys() {
+            resolver.material(name)?;
+        }
+        for (index, object) in self.objects.iter().enumerate() {
+            let label = match &object.name {
+                Some(name) => format!("objects[{}] ({:?})", index, name),
+                None => format!("objects[{}]", index),
+            };
+
+            objects.add(resolver.object(object, &label, None)?);
+        }
+
+        let background: Arc<dyn Background> = match &self.background {
+            None => Arc::new(GradientBackground::default()),
+            Some(BackgroundDescription::Solid { color }) => Arc::new(SolidBackground::new(*color)),
+            Some(BackgroundDescription::Gradient { bottom, top }) => {
+                Arc::new(GradientBackground::new(*bottom, *top))
+            }
+            Some(BackgroundDescription::Environment { file }) => Arc::new(EnvironmentMap::from(
+                resolver
+                    .image(file, ColorSpace::Srgb)
+                    .context("background")?,
+            )),
+        };
+        let mut world = HittableList::new();
+
+        if !objects.objects.is_empty() {
+            world.add(Arc::new(LinearBvh::from(&objects)));
+        }
+
+        Ok(Scene::new(
+            self.camera.clone().background(background),
+            world,
+        ))
+    }
+}
+
+impl Resolver<'_> {
+    fn texture(&mut self, name: &str) -> Result<Arc<dyn Texture>> {
+        if let Some(tex) = self.textures.get(name) {
+            return Ok(tex.clone());
+        }
+
+        let description = match self.description.textures.get(name) {
+            Some(description) => description,
+            None => bail!(RTError::InvalidScene(format!("unknown texture {:?}", name))),
+        };
+
+        if self.pending.iter().any(|pending| pending == name) {
+            bail!(RTError::InvalidScene(format!(
+                "texture {:?} refers to itself",
+                name
+            )));
+        }
+
+        self.pending.push(name.to_string());
+
+        let tex: Result<Arc<dyn Texture>> = match description {
+            TextureDescription::Solid { color } => Ok(Arc::new(SolidColor::new(*color))),
+            TextureDescription::Checker { scale, even, odd } => self
+                .texture_reference(even)
+                .and_then(|even| Ok((even, self.texture_reference(odd)?)))
+                .map(|(even, odd)| {
+                    Arc::new(CheckerTexture::from(*scale, &even, &odd)) as Arc<dyn Texture>
+                }),
+            TextureDescription::Image { file, color_space } => self.image(file, *color_space),
+            TextureDescription::Noise { scale, seed } => {
+                Ok(Arc::new(NoiseTexture::new(*scale, &mut Rng::new(*seed))))
+            }
+        };
+
+        self.pending.pop();
+
+        let tex = tex.with_context(|| format!("texture {:?}", name))?;
+
+        self.textures.insert(name.to_string(), tex.clone());
+
+        Ok(tex)
+    }
+
+    fn texture_reference(&mut self, reference: &TextureReference) -> Result<Arc<dyn Texture>> {
+        match reference {
+            TextureReference::Color(color) => Ok(Arc::new(SolidColor::new(*color))),
+            TextureReference::Named(name) => self.texture(name),
+        }
+    }
+
+    fn image(&self, file: &str, color_space: ColorSpace) -> Result<Arc<dyn Texture>> {
+        let joined = self.base_dir.join(file);
+        let tex = ImageTexture::with_color_space(joined.to_string_lossy().as_ref(), color_space)
+            .or_else(|_| ImageTexture::with_color_space(file, color_space))
+            .with_context(|| format!("failed to load image {:?}", file))?;
+
+        Ok(Arc::new(tex))
+    }
+
+    fn material(&mut self, name: &str) -> Result<Arc<dyn Material>> {
+        if let Some(mat) = self.materials.get(name) {
+            return Ok(mat.clone());
+        }
+
+        let description = match self.description.materials.get(name) {
+            Some(description) => description,
+            None => bail!(RTError::InvalidScene(format!(
+                "unknown material {:?}",
+                name
+            ))),
+        };
+        let mat: Result<Arc<dyn Material>> = match description {
+            MaterialDescription::Lambertian { albedo } => self
+                .texture_reference(albedo)
+                .map(|tex| Arc::new(Lambertian::from(tex)) as Arc<dyn Material>),
+            MaterialDescription::Metal { albedo, fuzz } => Ok(Arc::new(Metal::new(*albedo, *fuzz))),
+            MaterialDescription::Dielectric { refraction_index } => {
+                if *refraction_index <= 0_f64 {
+                    Err(RTError::InvalidScene(format!(
+                        "refraction_index must be positive, found {}",
+                        refraction_index
+                    ))
+                    .into())
+                } else {
+                    Ok(Arc::new(Dielectric::new(*refraction_index)))
+                }
+            }
+            MaterialDescription::DiffuseLight { emit } => self
+                .texture_reference(emit)
+                .map(|tex| Arc::new(DiffuseLight::from(tex)) as Arc<dyn Material>),
+            MaterialDescription::Isotropic { albedo } => self
+                .texture_reference(albedo)
+                .map(|tex| Arc::new(Isotropic::from(tex)) as Arc<dyn Material>),
+        };
+        let mat = mat.with_context(|| format!("material {:?}", name))?;
+
+        self.materials.insert(name.to_string(), mat.clone());
+
+        Ok(mat)
+    }
+
+    /// `fallback` stands in for a missing `material`, which only boundaries of
+    /// volumes may omit.
+    fn object(
+        &mut self,
+        object: &ObjectDescription,
+        label: &str,
+        fallback: Option<&Arc<dyn Material>>,
+    ) -> Result<Arc<dyn Hittable>> {
+        let shape = self
+            .shape(object, fallback)
+            .with_context(|| label.to_string())?;
+        let shape: Arc<dyn Hittable> = match object.rotate_y {
+            Some(angle) => Arc::new(RotateY::new(shape, angle)),
+            None => shape,
+        };
+
+        Ok(match object.translate {
+            Some(offset) => Arc::new(Translate::new(shape, offset)),
+            None => shape,
+        })
+    }
+
+    fn shape(
+        &mut self,
+        object: &ObjectDescription,
+        fallback: Option<&Arc<dyn Material>>,
+    ) -> Result<Arc<dyn Hittable>> {
+        Ok(match &object.shape {
+            ShapeDescription::Sphere {
+                center,
+                center2,
+                radius,
+            } => {
+                if *radius <= 0_f64 {
+                    bail!(RTError::InvalidScene(format!(
+                        "radius must be positive, found {}",
+                        radius
+                    )));
+                }
+
+                let mat = self.object_material(object, fallback)?;
+
+                match center2 {
+                    Some(center2) => Arc::new(Sphere::new_moving(*center, *center2, *radius, mat)),
+                    None => Arc::new(Sphere::new(*center, *radius, mat)),
+                }
+            }
+            ShapeDescription::Quad { q, u, v } => {
+                if vec3::cross(u, v).near_zero() {
+                    bail!(RTError::InvalidScene(
+                        "quad has zero area: u and v are parallel".into()
+                    ));
+                }
+
+                Arc::new(Quad::new(
+                    *q,
+                    *u,
+                    *v,
+                    self.object_material(object, fallback)?,
+                ))
+            }
+            ShapeDescription::Box { a, b } => {
+                let size = *b - a;
+
+                if [
+                    Vec3::new(size.x(), 0_f64, 0_f64),
+                    Vec3::new(0_f64, size.y(), 0_f64),
+                    Vec3::new(0_f64, 0_f64, size.z()),
+                ]
+                .iter()
+                .any(Vec3::near_zero)
+                {
+                    bail!(RTError::InvalidScene(
+                        "box has faces of zero area: a and b share a coordinate".into()
+                    ));
+                }
+
+                Arc::new(hittable::make_box(
+                    a,
+                    b,
+                    self.object_material(object, fallback)?,
+                ))
+            }
+            ShapeDescription::Triangle { a, b, c } => {
+                if vec3::cross(&(*b - a), &(*c - a)).near_zero() {
+                    bail!(RTError::InvalidScene(
+                        "triangle has zero area: its vertices are collinear".into()
+                    ));
+                }
+
+                Arc::new(Triangle::new(
+                    *a,
+                    *b,
+                    *c,
+                    self.object_material(object, fallback)?,
+                ))
+            }
+            ShapeDescription::Obj { file } => {
+                if object.material.is_some() {
+                    bail!(RTError::InvalidScene(
+                        "obj objects take their materials from the MTL files, not `material`"
+                            .into()
+                    ));
+                }
+
+                let joined = self.base_dir.join(file);
+                let path = if joined.exists() {
+                    joined.to_string_lossy().to_string()
+                } else {
+                    file.clone()
+                };
+                let meshes = loader::load_obj(&path)
+                    .with_context(|| format!("failed to load obj {:?}", file))?;
+
+                Arc::new(LinearBvh::from(&meshes))
+            }
+            ShapeDescription::ConstantMedium {
+                boundary,
+                density,
+                albedo,
+            } => {
+                if object.material.is_some() {
+                    bail!(RTError::InvalidScene(
+                        "constant_medium objects are colored by `albedo`, not `material`".into()
+                    ));
+                }
+                if *density <= 0_f64 {
+                    bail!(RTError::InvalidScene(format!(
+                        "density must be positive, found {}",
+                        density
+                    )));
+                }
+
+                let tex = self.texture_reference(albedo)?;
+                let phase_function: Arc<dyn Material> = Arc::new(Isotropic::from(tex.clone()));
+                let boundary = self.object(boundary, "boundary", Some(&phase_function))?;
+
+                Arc::new(ConstantMedium::from(boundary, *density, tex))
+            }
+        })
+    }
+
+    fn object_material(
+        &mut self,
+        object: &ObjectDescription,
+        fallback: Option<&Arc<dyn Material>>,
+    ) -> Result<Arc<dyn Material>> {
+        match (&object.material, fallback) {
+            (Some(name), _) => self.material(name),
+            (None, Some(mat)) => Ok(mat.clone()),
+            (None, None) => bail!(RTError::InvalidScene("missing material".into())),
+        }
+    }
+}
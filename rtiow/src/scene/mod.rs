@@ -0,0 +1,264 @@
+use crate::{
+    camera::{Camera, CameraBuilder},
+    hittable::HittableList,
+};
+use anyhow::{Context, Result};
+use std::{fs, path::Path};
+
+mod description;
+
+#[derive(Debug, Clone)]
+pub struct Scene {
+    pub camera: CameraBuilder,
+    pub world: HittableList,
+}
+
+impl Scene {
+    pub fn new(camera: CameraBuilder, world: HittableList) -> Self {
+        Self { camera, world }
+    }
+
+    /// Loads a TOML scene file. Image textures, environment maps and OBJ
+    /// meshes are looked up relative to the scene file first.
+    pub fn load(path: &str) -> Result<Self> {
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("failed to read scene file {:?}", path))?;
+        let base_dir = Path::new(path).parent().unwrap_or(Path::new(""));
+
+        Self::from_toml(&source, base_dir).with_context(|| format!("in scene file {:?}", path))
+    }
+
+    pub fn from_toml(source: &str, base_dir: &Path) -> Result<Self> {
+        let description = toml::from_str::<description::SceneDescription>(source)?;
+
+        description.build(base_dir)
+    }
+
+    pub fn camera(&self) -> Result<Camera> {
+        self.camera.build()
+    }
+}
+
+#[test]
+fn test_scene_from_toml() {
+    use crate::{
+        hittable::Hittable,
+        interval::Interval,
+        ray::Ray,
+        sampler::{IndependentSampler, SampleStream},
+        vec3::{Point3, Vec3},
+        INFINITY,
+    };
+
+    let sampler = IndependentSampler::new(0);
+    let mut samples = SampleStream::new(&sampler, 0, 0);
+
+    let source = r#"
+[camera]
+image_width = 40
+look_from = [0, 0, 5]
+look_at = [0, 0, 0]
+denoise = { iterations = 2, sigma_color = 0.5 }
+
+[background]
+type = "solid"
+color = [0, 0, 0]
+
+[textures.checker]
+type = "checker"
+scale = 0.5
+even = "white"
+odd = [0.2, 0.3, 0.1]
+
+[textures.white]
+type = "solid"
+color = [0.9, 0.9, 0.9]
+
+[materials.ground]
+type = "lambertian"
+albedo = "checker"
+
+[materials.glass]
+type = "dielectric"
+refraction_index = 1.5
+
+[[objects]]
+material = "glass"
+shape = { type = "sphere", center = [0, 0, 0], radius = 1 }
+
+[[objects]]
+material = "ground"
+rotate_y = 45
+translate = [0, -3, 0]
+shape = { type = "box", a = [-1, -1, -1], b = [1, 1, 1] }
+
+[[objects]]
+
+[objects.shape]
+type = "constant_medium"
+density = 0.5
+albedo = [1, 1, 1]
+boundary = { shape = { type = "sphere", center = [3, 0, 0], radius = 0.5 } }
+"#;
+    let scene = Scene::from_toml(source, Path::new("")).expect("valid scene");
+    let r = Ray::new(
+        Point3::new(0_f64, 0_f64, 5_f64),
+        Vec3::new(0_f64, 0_f64, -1_f64),
+        0_f64,
+    );
+    let rec = scene
+        .world
+        .hit(&r, &Interval::new(0.001, INFINITY), &mut samples)
+        .expect("ray should hit the glass sphere");
+
+    assert_eq!(
+        [40, 40],
+        scene.camera().expect("camera is valid").image_size()
+    );
+    assert!((rec.t - 4_f64).abs() < 1e-9);
+}
+
+#[test]
+fn test_scene_error_names_entry() {
+    use crate::error::RTError;
+
+    let source = r#"
+[materials.red]
+type = "lambertian"
+albedo = [0.8, 0.1, 0.1]
+
+[[objects]]
+material = "red"
+shape = { type = "sphere", center = [0, 0, 0], radius = 1 }
+
+[[objects]]
+name = "floor"
+material = "rde"
+shape = { type = "quad", q = [-5, -1, -5], u = [10, 0, 0], v = [0, 0, 10] }
+"#;
+    let err = Scene::from_toml(source, Path::new("")).expect_err("material is misspelled");
+    let message = format!("{:#}", err);
+
+    assert!(matches!(
+        err.downcast_ref::<RTError>(),
+        Some(RTError::InvalidScene(_))
+    ));
+    assert!(message.contains("objects[1] (\"floor\")"), "{}", message);
+    assert!(message.contains("\"rde\""), "{}", message);
+}
+
+#[test]
+fn test_scene_rejects_texture_cycles() {
+    let source = r#"
+[textures.a]
+type = "checker"
+scale = 1
+even = "b"
+odd = [0, 0, 0]
+
+[textures.b]
+type = "checker"
+scale = 1
+even = "a"
+odd = [0, 0, 0]
+"#;
+    let err = Scene::from_toml(source, Path::new("")).expect_err("textures form a cycle");
+
+    assert!(format!("{:#}", err).contains("refers to itself"));
+}
+
+#[test]
+fn test_scene_rejects_unknown_object_fields() {
+    for object in [
+        r#"rotate_yy = 30
+shape = { type = "sphere", center = [0, 0, 0], radius = 1 }"#,
+        r#"shape = { type = "sphere", center = [0, 0, 0], radius = 1, materail = "red" }"#,
+    ] {
+        let source = format!("[[objects]]\nmaterial = \"red\"\n{}\n", object);
+        let err = Scene::from_toml(&source, Path::new("")).expect_err("field is misspelled");
+        let message = format!("{:#}", err);
+
+        assert!(message.contains("unknown field"), "{}", message);
+    }
+}
+
+#[test]
+fn test_scene_rejects_ignored_materials() {
+    use crate::error::RTError;
+
+    for shape in [
+        r#"{ type = "obj", file = "missing.obj" }"#,
+        r#"{ type = "constant_medium", density = 1, albedo = [1, 1, 1], boundary = { shape = { type = "sphere", center = [0, 0, 0], radius = 1 } } }"#,
+    ] {
+        let source = format!(
+            "[materials.red]\ntype = \"lambertian\"\nalbedo = [0.8, 0.1, 0.1]\n\n\
+             [[objects]]\nmaterial = \"red\"\nshape = {}\n",
+            shape
+        );
+        let err = Scene::from_toml(&source, Path::new("")).expect_err("material is ignored");
+        let message = format!("{:#}", err);
+
+        assert!(matches!(
+            err.downcast_ref::<RTError>(),
+            Some(RTError::InvalidScene(_))
+        ));
+        assert!(message.contains("not `material`"), "{}", message);
+    }
+}
+
+#[test]
+fn test_scene_rejects_degenerate_shapes() {
+    use crate::error::RTError;
+
+    for (name, shape) in [
+        (
+            "flat",
+            r#"{ type = "quad", q = [0, 0, 0], u = [1, 0, 0], v = [2, 0, 0] }"#,
+        ),
+        (
+            "sliver",
+            r#"{ type = "triangle", a = [0, 0, 0], b = [1, 1, 1], c = [2, 2, 2] }"#,
+        ),
+        ("slab", r#"{ type = "box", a = [0, 0, 0], b = [1, 0, 1] }"#),
+    ] {
+        let source = format!(
+            "[materials.red]\ntype = \"lambertian\"\nalbedo = [0.8, 0.1, 0.1]\n\n\
+             [[objects]]\nname = \"{}\"\nmaterial = \"red\"\nshape = {}\n",
+            name, shape
+        );
+        let err = Scene::from_toml(&source, Path::new("")).expect_err("shape has no area");
+        let message = format!("{:#}", err);
+
+        assert!(matches!(
+            err.downcast_ref::<RTError>(),
+            Some(RTError::InvalidScene(_))
+        ));
+        assert!(
+            message.contains(&format!("objects[0] (\"{}\")", name)),
+            "{}",
+            message
+        );
+        assert!(message.contains("zero area"), "{}", message);
+    }
+}
+
+#[test]
+fn test_scene_rejects_non_positive_radius() {
+    use crate::error::RTError;
+
+    for radius in ["-1", "0"] {
+        let source = format!(
+            "[materials.red]\ntype = \"lambertian\"\nalbedo = [0.8, 0.1, 0.1]\n\n\
+             [[objects]]\nmaterial = \"red\"\n\
+             shape = {{ type = \"sphere\", center = [0, 0, 0], radius = {} }}\n",
+            radius
+        );
+        let err = Scene::from_toml(&source, Path::new("")).expect_err("sphere has no surface");
+
+        assert!(matches!(
+            err.downcast_ref::<RTError>(),
+            Some(RTError::InvalidScene(_))
+        ));
+        assert!(format!("{:#}", err).contains("radius must be positive"));
+    }
+}
@@ -1,13 +1,26 @@
+use crate::vec3::{self, Color};
 use anyhow::{bail, Result};
+use serde::Deserialize;
 use stb_image::image::{self as stbi, LoadResult};
 use std::env::var;
 
+/// How the 8-bit samples of an image are encoded. Color images are almost
+/// always sRGB; data such as normal or roughness maps is stored linearly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorSpace {
+    #[default]
+    Srgb,
+    Linear,
+}
+
 #[derive(Debug)]
 pub struct RtwImage {
-    _fdata: Option<Box<[f32]>>,
+    fdata: Option<Box<[f32]>>,
     bdata: Box<[u8]>,
     image_size: [usize; 2],
     bytes_per_scanlines: usize,
+    color_space: ColorSpace,
 }
 
 impl<'a> RtwImage {
@@ -90,13 +103,21 @@ impl<'a> RtwImage {
         };
 
         Ok(Self {
-            _fdata: fdata,
+            fdata,
             bdata,
             image_size,
             bytes_per_scanlines,
+            color_space: ColorSpace::default(),
         })
     }
 
+    /// Sets how `pixel_color` decodes the 8-bit samples. Floating-point
+    /// images such as `.hdr` files are always linear.
+    pub fn color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
     pub fn width(&self) -> usize {
         self.image_size[0]
     }
@@ -112,6 +133,30 @@ impl<'a> RtwImage {
         &self.bdata[y * self.bytes_per_scanlines + x * Self::BYTES_PER_PIXEL..self.bdata.len()]
     }
 
+    /// Linear color of the pixel at (`x`, `y`), clamped to the image.
+    pub fn pixel_color(&self, x: i32, y: i32) -> Color {
+        if let Some(fdata) = &self.fdata {
+            let x = Self::clamp(x, 0, self.image_size[0] as i32) as usize;
+            let y = Self::clamp(y, 0, self.image_size[1] as i32) as usize;
+            let offset = y * self.bytes_per_scanlines + x * Self::BYTES_PER_PIXEL;
+            let pixel = &fdata[offset..offset + Self::BYTES_PER_PIXEL];
+
+            return Color::new(pixel[0] as f64, pixel[1] as f64, pixel[2] as f64);
+        }
+
+        let pixel = self.pixel_data(x, y);
+        let decode = |byte: u8| {
+            let value = byte as f64 / 255_f64;
+
+            match self.color_space {
+                ColorSpace::Srgb => vec3::srgb_to_linear(value),
+                ColorSpace::Linear => value,
+            }
+        };
+
+        Color::new(decode(pixel[0]), decode(pixel[1]), decode(pixel[2]))
+    }
+
     fn clamp(x: i32, low: i32, high: i32) -> i32 {
         if x < low {
             low
@@ -141,3 +186,25 @@ impl<'a> RtwImage {
         Box::from(bdata.as_slice())
     }
 }
+
+#[test]
+fn test_rtw_image_pixel_color() {
+    let image = RtwImage {
+        fdata: None,
+        bdata: Box::new([0, 128, 255, 188, 188, 188]),
+        image_size: [2, 1],
+        bytes_per_scanlines: 6,
+        color_space: ColorSpace::Srgb,
+    };
+    let srgb = image.pixel_color(0, 0);
+
+    assert_eq!(0_f64, srgb.x());
+    assert!((srgb.y() - 0.215861).abs() < 1e-6);
+    assert_eq!(1_f64, srgb.z());
+    // sRGB 188 is roughly linear 0.5
+    assert!((image.pixel_color(5, 0).x() - 0.5).abs() < 0.01);
+
+    let linear = image.color_space(ColorSpace::Linear).pixel_color(0, 0);
+
+    assert_eq!(128_f64 / 255_f64, linear.y());
+}
@@ -1,11 +1,11 @@
-use crate::{interval::Interval, vec3::Color};
+use crate::{interval::Interval, utils::Rng, vec3::Color};
 use anyhow::Result;
 use std::{fmt::Debug, sync::Arc};
 
 mod image;
 mod perlin;
 
-pub use image::RtwImage;
+pub use image::{ColorSpace, RtwImage};
 pub use perlin::*;
 
 pub trait Texture: Debug + Send + Sync {
@@ -92,9 +92,16 @@ impl Texture for CheckerTexture {
 }
 
 impl ImageTexture {
+    /// Loads a color image, decoding 8-bit samples as sRGB.
     pub fn new(filename: &str) -> Result<Self> {
+        Self::with_color_space(filename, ColorSpace::Srgb)
+    }
+
+    /// Loads an image whose 8-bit samples are encoded in `color_space`; use
+    /// `ColorSpace::Linear` for data textures such as normal maps.
+    pub fn with_color_space(filename: &str, color_space: ColorSpace) -> Result<Self> {
         Ok(Self {
-            image: RtwImage::new(filename)?,
+            image: RtwImage::new(filename)?.color_space(color_space),
         })
     }
 }
@@ -109,21 +116,15 @@ impl Texture for ImageTexture {
         let v = 1_f64 - Interval::new(0_f64, 1_f64).clamp(v);
         let i = (u * self.image.width() as f64) as i32;
         let j = (v * self.image.height() as f64) as i32;
-        let pixel = self.image.pixel_data(i, j);
-        let color_scale = 1_f64 / 255_f64;
-
-        Color::new(
-            color_scale * pixel[0] as f64,
-            color_scale * pixel[1] as f64,
-            color_scale * pixel[2] as f64,
-        )
+
+        self.image.pixel_color(i, j)
     }
 }
 
 impl NoiseTexture {
-    pub fn new(scale: f64) -> Self {
+    pub fn new(scale: f64, rng: &mut Rng) -> Self {
         Self {
-            noise: Perlin::new(),
+            noise: Perlin::new(rng),
             scale,
         }
     }
@@ -1,6 +1,6 @@
 use crate::{
     interval::Interval,
-    utils::random_i32,
+    utils::Rng,
     vec3::{self, Point3, Vec3},
 };
 
@@ -15,19 +15,19 @@ pub struct Perlin {
 impl Perlin {
     const POINT_COUNT: usize = 256;
 
-    pub fn new() -> Self {
+    pub fn new(rng: &mut Rng) -> Self {
         let mut randvec = [Vec3::zeroes(); Self::POINT_COUNT];
         let range = Interval::new(-1_f64, 1_f64);
 
         randvec
             .iter_mut()
-            .for_each(|p| *p = vec3::unit_vector(&Vec3::random_in_range(&range)));
+            .for_each(|p| *p = vec3::unit_vector(&Vec3::random_in_range(&range, rng)));
 
         Self {
             randvec,
-            perm_x: Self::perlin_generate_perm(),
-            perm_y: Self::perlin_generate_perm(),
-            perm_z: Self::perlin_generate_perm(),
+            perm_x: Self::perlin_generate_perm(rng),
+            perm_y: Self::perlin_generate_perm(rng),
+            perm_z: Self::perlin_generate_perm(rng),
         }
     }
 
@@ -72,21 +72,21 @@ impl Perlin {
         accum.abs()
     }
 
-    fn perlin_generate_perm() -> [usize; Self::POINT_COUNT] {
+    fn perlin_generate_perm(rng: &mut Rng) -> [usize; Self::POINT_COUNT] {
         let mut ret = [0; Self::POINT_COUNT];
 
         ret.iter_mut().enumerate().for_each(|(i, p)| *p = i);
 
-        Self::permute(&mut ret, Self::POINT_COUNT);
+        Self::permute(&mut ret, Self::POINT_COUNT, rng);
 
         ret
     }
 
-    fn permute(p: &mut [usize], n: usize) {
+    fn permute(p: &mut [usize], n: usize, rng: &mut Rng) {
         assert!(p.len() >= n);
 
         for i in (n..0).rev() {
-            let target = random_i32(&Interval::new(0_f64, i as f64)) as usize;
+            let target = rng.random_i32(&Interval::new(0_f64, i as f64)) as usize;
             let tmp = p[i];
 
             p[i] = p[target];
@@ -119,3 +119,12 @@ impl Perlin {
         accum
     }
 }
+
+#[test]
+fn test_perlin_is_drawn_from_generator() {
+    let p = Point3::new(0.3, 1.7, 2.2);
+    let noise = |seed: u64| Perlin::new(&mut Rng::new(seed)).noise(&p);
+
+    assert_eq!(noise(3), noise(3));
+    assert_ne!(noise(3), noise(4));
+}
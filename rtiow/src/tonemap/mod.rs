@@ -0,0 +1,118 @@
+use crate::vec3::{self, Color};
+
+/// Curve that compresses linear radiance into the displayable `[0, 1]`
+/// range before quantization.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ToneMapOperator {
+    /// Leaves the color untouched, so values above one clip to white.
+    #[default]
+    None,
+    /// `L / (1 + L)` on the luminance, which never reaches white.
+    Reinhard,
+    /// Reinhard with a white point: luminance at or above `white` maps to
+    /// white.
+    ReinhardExtended { white: f64 },
+    /// Krzysztof Narkowicz's fit of the ACES filmic curve, applied per
+    /// channel.
+    Aces,
+}
+
+/// Exposure compensation followed by a tone-mapping curve. The default does
+/// nothing, matching the untouched output of earlier renders.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ToneMapping {
+    pub operator: ToneMapOperator,
+    /// Exposure compensation in stops; every stop doubles the radiance.
+    pub exposure: f64,
+}
+
+impl ToneMapping {
+    pub fn new(operator: ToneMapOperator) -> Self {
+        Self {
+            operator,
+            exposure: 0_f64,
+        }
+    }
+
+    pub fn exposure(mut self, stops: f64) -> Self {
+        self.exposure = stops;
+        self
+    }
+
+    pub fn apply(&self, color: &Color) -> Color {
+        let color = 2_f64.powf(self.exposure) * *color;
+
+        match self.operator {
+            ToneMapOperator::None => color,
+            ToneMapOperator::Reinhard => Self::scale_luminance(&color, |l| l / (1_f64 + l)),
+            ToneMapOperator::ReinhardExtended { white } => {
+                let white_squared = white * white;
+
+                Self::scale_luminance(&color, |l| l * (1_f64 + l / white_squared) / (1_f64 + l))
+            }
+            ToneMapOperator::Aces => {
+                let aces = |x: f64| {
+                    let x = x.max(0_f64);
+
+                    ((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)).clamp(0_f64, 1_f64)
+                };
+
+                Color::new(aces(color.x()), aces(color.y()), aces(color.z()))
+            }
+        }
+    }
+
+    /// Maps the luminance of `color` through `curve` and scales the color to
+    /// match, which keeps its hue.
+    fn scale_luminance(color: &Color, curve: impl Fn(f64) -> f64) -> Color {
+        let luminance = vec3::luminance(color);
+
+        if luminance <= 0_f64 {
+            return Color::zeroes();
+        }
+
+        (curve(luminance) / luminance) * *color
+    }
+}
+
+#[test]
+fn test_tone_mapping_default_is_identity() {
+    let color = Color::new(0.25, 3_f64, 12_f64);
+
+    assert_eq!(color, ToneMapping::default().apply(&color));
+}
+
+#[test]
+fn test_tone_mapping_operators() {
+    let gray = |v: f64| Color::new(v, v, v);
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+
+    let reinhard = ToneMapping::new(ToneMapOperator::Reinhard);
+
+    assert!(close(0.5, reinhard.apply(&gray(1_f64)).x()));
+    assert!(close(0.75, reinhard.apply(&gray(3_f64)).y()));
+
+    let extended = ToneMapping::new(ToneMapOperator::ReinhardExtended { white: 4_f64 });
+
+    assert!(close(1_f64, extended.apply(&gray(4_f64)).z()));
+    assert!(extended.apply(&gray(2_f64)).x() > reinhard.apply(&gray(2_f64)).x());
+
+    let aces = ToneMapping::new(ToneMapOperator::Aces);
+
+    assert_eq!(0_f64, aces.apply(&gray(0_f64)).x());
+    assert!(aces.apply(&gray(100_f64)).x() <= 1_f64);
+    assert!(aces.apply(&gray(0.5)).x() < aces.apply(&gray(1_f64)).x());
+
+    let exposed = ToneMapping::default().exposure(2_f64);
+
+    assert!(close(2_f64, exposed.apply(&gray(0.5)).x()));
+}
+
+#[test]
+fn test_reinhard_keeps_hue() {
+    let color = Color::new(4_f64, 2_f64, 1_f64);
+    let mapped = ToneMapping::new(ToneMapOperator::Reinhard).apply(&color);
+
+    assert!((mapped.x() / mapped.y() - 2_f64).abs() < 1e-9);
+    assert!((mapped.y() / mapped.z() - 2_f64).abs() < 1e-9);
+}
@@ -1,24 +1,18 @@
-use crate::{interval::Interval, PI};
-use rand::prelude::*;
+use crate::PI;
 
-#[inline]
-pub fn degrees_to_radians(degrees: f64) -> f64 {
-    degrees * PI / 180_f64
-}
+mod rng;
 
-#[inline]
-pub fn random() -> f64 {
-    thread_rng().gen()
-}
+pub use rng::*;
 
 #[inline]
-pub fn random_in_range(range: &Interval) -> f64 {
-    range.min + (range.max - range.min) * thread_rng().gen::<f64>()
+pub fn degrees_to_radians(degrees: f64) -> f64 {
+    degrees * PI / 180_f64
 }
 
-#[inline]
-pub fn random_i32(range: &Interval) -> i32 {
-    random_in_range(&Interval::new(range.min, range.max + 1_f64)) as i32
+/// A fresh seed from the operating system's entropy, for runs that are not
+/// given one.
+pub fn random_seed() -> u64 {
+    rand::random()
 }
 
 #[test]
@@ -0,0 +1,77 @@
+use crate::interval::Interval;
+
+/// SplitMix64 generator. Samplers start one from a state hashed from the
+/// seed, pixel, sample index and dimension, and scenes are built from one
+/// seeded explicitly, so the numbers drawn never depend on which thread
+/// draws them or in what order.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// A generator whose state is the hash of `values`.
+    pub fn hashed(values: &[u64]) -> Self {
+        Self::new(hash(values))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(Self::GOLDEN_GAMMA);
+
+        mix64(self.state)
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub fn random(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+
+    pub fn random_in_range(&mut self, range: &Interval) -> f64 {
+        range.min + (range.max - range.min) * self.random()
+    }
+
+    /// Uniform integer in `[range.min, range.max]`.
+    pub fn random_i32(&mut self, range: &Interval) -> i32 {
+        self.random_in_range(&Interval::new(range.min, range.max + 1_f64)) as i32
+    }
+}
+
+/// Combines `values` into one well-mixed word; the order of the values
+/// matters.
+pub fn hash(values: &[u64]) -> u64 {
+    values
+        .iter()
+        .fold(Rng::GOLDEN_GAMMA, |hash, value| mix64(hash ^ mix64(*value)))
+}
+
+/// Finalizer of SplitMix64.
+fn mix64(z: u64) -> u64 {
+    let z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    let z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+    z ^ (z >> 31)
+}
+
+#[test]
+fn test_hashed_rng_streams() {
+    let draw = |values: &[u64]| {
+        let mut rng = Rng::hashed(values);
+
+        (0..4).map(|_| rng.random()).collect::<Vec<f64>>()
+    };
+
+    assert_eq!(draw(&[1, 2, 3]), draw(&[1, 2, 3]));
+    assert_ne!(draw(&[1, 2, 3]), draw(&[1, 3, 2]));
+    assert_ne!(draw(&[1, 2, 3]), draw(&[2, 2, 3]));
+
+    let mut rng = Rng::new(0);
+    let mean = (0..10_000).map(|_| rng.random()).sum::<f64>() / 1e4;
+
+    assert!((mean - 0.5).abs() < 0.01, "{}", mean);
+}
@@ -5,13 +5,32 @@ static INTENSITY: Interval = Interval {
     min: 0_f64,
     max: 0.999,
 };
+static INTENSITY_16: Interval = Interval {
+    min: 0_f64,
+    max: 0.99999,
+};
 
+/// Encodes a linear component with the sRGB transfer function.
 #[inline]
-pub fn linear_to_gamma(linear_component: f64) -> f64 {
-    if linear_component > 0_f64 {
-        linear_component.sqrt()
+pub fn linear_to_srgb(linear_component: f64) -> f64 {
+    if linear_component <= 0_f64 {
+        0_f64
+    } else if linear_component <= 0.0031308 {
+        12.92 * linear_component
     } else {
+        1.055 * linear_component.powf(1_f64 / 2.4) - 0.055
+    }
+}
+
+/// Decodes an sRGB-encoded component back to linear.
+#[inline]
+pub fn srgb_to_linear(srgb_component: f64) -> f64 {
+    if srgb_component <= 0_f64 {
         0_f64
+    } else if srgb_component <= 0.04045 {
+        srgb_component / 12.92
+    } else {
+        ((srgb_component + 0.055) / 1.055).powf(2.4)
     }
 }
 
@@ -19,29 +38,57 @@ pub fn write_color(pixel_color: &Color) -> [i32; 3] {
     let mut rgb = [0; 3];
 
     rgb.iter_mut().enumerate().for_each(|(i, e_i)| {
-        *e_i = (256_f64 * INTENSITY.clamp(linear_to_gamma(pixel_color[i]))) as i32
+        *e_i = (256_f64 * INTENSITY.clamp(linear_to_srgb(pixel_color[i]))) as i32
+    });
+
+    rgb
+}
+
+/// Same as `write_color`, but quantized to the full 16-bit range.
+pub fn write_color_16(pixel_color: &Color) -> [u16; 3] {
+    let mut rgb = [0; 3];
+
+    rgb.iter_mut().enumerate().for_each(|(i, e_i)| {
+        *e_i = (65536_f64 * INTENSITY_16.clamp(linear_to_srgb(pixel_color[i]))) as u16
     });
 
     rgb
 }
 
+/// Relative luminance of a linear color using the Rec. 709 primaries.
+#[inline]
+pub fn luminance(color: &Color) -> f64 {
+    0.2126 * color.x() + 0.7152 * color.y() + 0.0722 * color.z()
+}
+
 #[test]
 fn test_write_color() {
-    let pixel_color = Color::new(0.01, 0.999, 0.9991);
+    let pixel_color = Color::new(0.001, 0.5, 0.9991);
     let result = write_color(&pixel_color);
-    let target = [25, 255, 255];
+    let target = [3, 188, 255];
 
     assert_eq!(target, result)
 }
 
 #[test]
-fn test_linear_to_gamma() {
-    let linear_component = [0_f64, 4_f64];
-    let targets = [0_f64, 2_f64];
+fn test_write_color_16() {
+    let pixel_color = Color::new(0_f64, 0.25, 1.5);
+    let result = write_color_16(&pixel_color);
+    let target = [0, 35199, 65535];
+
+    assert_eq!(target, result)
+}
 
-    for i in 0..linear_component.len() {
-        let gamma = linear_to_gamma(linear_component[i]);
+#[test]
+fn test_srgb_round_trip() {
+    for i in 0..=100 {
+        let linear = i as f64 / 100_f64;
+        let encoded = linear_to_srgb(linear);
 
-        assert_eq!(targets[i], gamma)
+        assert!((srgb_to_linear(encoded) - linear).abs() < 1e-12);
     }
+
+    assert_eq!(0_f64, linear_to_srgb(-1_f64));
+    assert!((linear_to_srgb(0.0031308) - 0.04045).abs() < 1e-6);
+    assert!((srgb_to_linear(0.5) - 0.214041).abs() < 1e-6);
 }
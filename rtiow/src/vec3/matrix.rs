@@ -0,0 +1,357 @@
+use super::{Point3, Vec3};
+use crate::utils;
+use std::ops::{Index, Mul};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat3 {
+    m: [[f64; 3]; 3],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat4 {
+    m: [[f64; 4]; 4],
+}
+
+impl Mat3 {
+    /// Singularity threshold relative to the magnitude of the entries, so
+    /// uniformly scaling a matrix does not change whether it is invertible.
+    const DELTA_SINGULAR: f64 = 1e-12;
+
+    pub fn new(m: [[f64; 3]; 3]) -> Self {
+        Self { m }
+    }
+
+    pub fn identity() -> Self {
+        let mut m = [[0_f64; 3]; 3];
+
+        m.iter_mut().enumerate().for_each(|(i, row)| row[i] = 1_f64);
+
+        Self { m }
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut m = [[0_f64; 3]; 3];
+
+        m.iter_mut().enumerate().for_each(|(i, row)| {
+            row.iter_mut()
+                .enumerate()
+                .for_each(|(j, m_ij)| *m_ij = self.m[j][i])
+        });
+
+        Self { m }
+    }
+
+    pub fn determinant(&self) -> f64 {
+        let m = &self.m;
+
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        let scale = max_abs(self.m.iter().flatten());
+
+        if det.abs() <= Self::DELTA_SINGULAR * scale.powi(3) {
+            return None;
+        }
+
+        let mut m = [[0_f64; 3]; 3];
+
+        // Cofactors of the transposed matrix, i.e. the adjugate.
+        m.iter_mut().enumerate().for_each(|(i, row)| {
+            row.iter_mut().enumerate().for_each(|(j, m_ij)| {
+                let r = [(j + 1) % 3, (j + 2) % 3];
+                let c = [(i + 1) % 3, (i + 2) % 3];
+
+                *m_ij = (self.m[r[0]][c[0]] * self.m[r[1]][c[1]]
+                    - self.m[r[0]][c[1]] * self.m[r[1]][c[0]])
+                    / det;
+            })
+        });
+
+        Some(Self { m })
+    }
+}
+
+impl Index<(usize, usize)> for Mat3 {
+    type Output = f64;
+
+    fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
+        &self.m[i][j]
+    }
+}
+
+impl Mul for Mat3 {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self::Output {
+        let mut m = [[0_f64; 3]; 3];
+
+        m.iter_mut().enumerate().for_each(|(i, row)| {
+            row.iter_mut().enumerate().for_each(|(j, m_ij)| {
+                *m_ij = (0..3).map(|k| self.m[i][k] * other.m[k][j]).sum();
+            })
+        });
+
+        Self { m }
+    }
+}
+
+impl Mul<Vec3> for Mat3 {
+    type Output = Vec3;
+
+    fn mul(self, v: Vec3) -> Self::Output {
+        let row = |i: usize| self.m[i][0] * v.x() + self.m[i][1] * v.y() + self.m[i][2] * v.z();
+
+        Vec3::new(row(0), row(1), row(2))
+    }
+}
+
+impl Mat4 {
+    /// Pivot threshold relative to the largest entry; see `Mat3`.
+    const DELTA_SINGULAR: f64 = 1e-12;
+
+    pub fn new(m: [[f64; 4]; 4]) -> Self {
+        Self { m }
+    }
+
+    pub fn identity() -> Self {
+        let mut m = [[0_f64; 4]; 4];
+
+        m.iter_mut().enumerate().for_each(|(i, row)| row[i] = 1_f64);
+
+        Self { m }
+    }
+
+    pub fn translation(offset: &Vec3) -> Self {
+        let mut ret = Self::identity();
+
+        (0..3).for_each(|i| ret.m[i][3] = offset[i]);
+
+        ret
+    }
+
+    pub fn scaling(factor: &Vec3) -> Self {
+        let mut ret = Self::identity();
+
+        (0..3).for_each(|i| ret.m[i][i] = factor[i]);
+
+        ret
+    }
+
+    pub fn rotation_x(angle: f64) -> Self {
+        let (sin_theta, cos_theta) = utils::degrees_to_radians(angle).sin_cos();
+
+        Self::new([
+            [1_f64, 0_f64, 0_f64, 0_f64],
+            [0_f64, cos_theta, -sin_theta, 0_f64],
+            [0_f64, sin_theta, cos_theta, 0_f64],
+            [0_f64, 0_f64, 0_f64, 1_f64],
+        ])
+    }
+
+    pub fn rotation_y(angle: f64) -> Self {
+        let (sin_theta, cos_theta) = utils::degrees_to_radians(angle).sin_cos();
+
+        Self::new([
+            [cos_theta, 0_f64, sin_theta, 0_f64],
+            [0_f64, 1_f64, 0_f64, 0_f64],
+            [-sin_theta, 0_f64, cos_theta, 0_f64],
+            [0_f64, 0_f64, 0_f64, 1_f64],
+        ])
+    }
+
+    pub fn rotation_z(angle: f64) -> Self {
+        let (sin_theta, cos_theta) = utils::degrees_to_radians(angle).sin_cos();
+
+        Self::new([
+            [cos_theta, -sin_theta, 0_f64, 0_f64],
+            [sin_theta, cos_theta, 0_f64, 0_f64],
+            [0_f64, 0_f64, 1_f64, 0_f64],
+            [0_f64, 0_f64, 0_f64, 1_f64],
+        ])
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut m = [[0_f64; 4]; 4];
+
+        m.iter_mut().enumerate().for_each(|(i, row)| {
+            row.iter_mut()
+                .enumerate()
+                .for_each(|(j, m_ij)| *m_ij = self.m[j][i])
+        });
+
+        Self { m }
+    }
+
+    pub fn inverse(&self) -> Option<Self> {
+        // Gauss-Jordan elimination with partial pivoting.
+        let mut a = self.m;
+        let mut inv = Self::identity().m;
+        let threshold = Self::DELTA_SINGULAR * max_abs(self.m.iter().flatten());
+
+        for col in 0..4 {
+            let pivot = (col..4)
+                .max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))
+                .unwrap_or(col);
+
+            if a[pivot][col].abs() <= threshold {
+                return None;
+            }
+
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+
+            let scale = 1_f64 / a[col][col];
+
+            a[col].iter_mut().for_each(|e| *e *= scale);
+            inv[col].iter_mut().for_each(|e| *e *= scale);
+
+            for row in 0..4 {
+                if row == col {
+                    continue;
+                }
+
+                let factor = a[row][col];
+
+                for k in 0..4 {
+                    a[row][k] -= factor * a[col][k];
+                    inv[row][k] -= factor * inv[col][k];
+                }
+            }
+        }
+
+        Some(Self { m: inv })
+    }
+
+    pub fn upper_left(&self) -> Mat3 {
+        let mut m = [[0_f64; 3]; 3];
+
+        m.iter_mut().enumerate().for_each(|(i, row)| {
+            row.iter_mut()
+                .enumerate()
+                .for_each(|(j, m_ij)| *m_ij = self.m[i][j])
+        });
+
+        Mat3 { m }
+    }
+
+    pub fn transform_point(&self, p: &Point3) -> Point3 {
+        let row = |i: usize| {
+            self.m[i][0] * p.x() + self.m[i][1] * p.y() + self.m[i][2] * p.z() + self.m[i][3]
+        };
+        let w = row(3);
+        let p = Point3::new(row(0), row(1), row(2));
+
+        if w == 1_f64 {
+            p
+        } else {
+            p / w
+        }
+    }
+
+    pub fn transform_vector(&self, v: &Vec3) -> Vec3 {
+        self.upper_left() * *v
+    }
+}
+
+impl Index<(usize, usize)> for Mat4 {
+    type Output = f64;
+
+    fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
+        &self.m[i][j]
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self::Output {
+        let mut m = [[0_f64; 4]; 4];
+
+        m.iter_mut().enumerate().for_each(|(i, row)| {
+            row.iter_mut().enumerate().for_each(|(j, m_ij)| {
+                *m_ij = (0..4).map(|k| self.m[i][k] * other.m[k][j]).sum();
+            })
+        });
+
+        Self { m }
+    }
+}
+
+fn max_abs<'a>(entries: impl Iterator<Item = &'a f64>) -> f64 {
+    entries.fold(0_f64, |max, e| max.max(e.abs()))
+}
+
+#[test]
+fn test_mat3_inverse() {
+    let m = Mat3::new([
+        [2_f64, 0_f64, 1_f64],
+        [1_f64, 3_f64, 2_f64],
+        [1_f64, 1_f64, 2_f64],
+    ]);
+    let result = m * m.inverse().expect("matrix is invertible");
+
+    for i in 0..3 {
+        for j in 0..3 {
+            let target = if i == j { 1_f64 } else { 0_f64 };
+
+            assert!((target - result[(i, j)]).abs() < 1e-12);
+        }
+    }
+    assert!(Mat3::new([[1_f64; 3]; 3]).inverse().is_none());
+}
+
+#[test]
+fn test_mat4_inverse_and_transpose() {
+    let m = Mat4::translation(&Vec3::new(1_f64, 2_f64, 3_f64))
+        * Mat4::rotation_y(30_f64)
+        * Mat4::scaling(&Vec3::new(2_f64, 3_f64, 4_f64));
+    let result = m * m.inverse().expect("matrix is invertible");
+
+    for i in 0..4 {
+        for j in 0..4 {
+            let target = if i == j { 1_f64 } else { 0_f64 };
+
+            assert!((target - result[(i, j)]).abs() < 1e-12);
+            assert_eq!(m[(i, j)], m.transpose()[(j, i)]);
+        }
+    }
+    assert!(Mat4::scaling(&Vec3::new(1_f64, 0_f64, 1_f64))
+        .inverse()
+        .is_none());
+}
+
+#[test]
+fn test_mat4_transform_point_and_vector() {
+    let m = Mat4::translation(&Vec3::new(1_f64, 2_f64, 3_f64))
+        * Mat4::scaling(&Vec3::new(2_f64, 2_f64, 2_f64));
+    let p = Point3::new(1_f64, 1_f64, 1_f64);
+
+    assert_eq!(Point3::new(3_f64, 4_f64, 5_f64), m.transform_point(&p));
+    assert_eq!(Vec3::new(2_f64, 2_f64, 2_f64), m.transform_vector(&p));
+}
+
+#[test]
+fn test_inverse_threshold_is_relative() {
+    // det = 1e-12, but the matrix is perfectly conditioned
+    let small = Mat4::scaling(&Vec3::new(1e-4, 1e-4, 1e-4));
+    let inverse = small.inverse().expect("small scales are invertible");
+
+    assert!((inverse[(0, 0)] - 1e4).abs() < 1e-8);
+    assert!(small.upper_left().inverse().is_some());
+
+    // det = 1e3, but the last axis is lost next to the others
+    let flat = Mat3::new([
+        [1e6, 0_f64, 0_f64],
+        [0_f64, 1e6, 0_f64],
+        [0_f64, 0_f64, 1e-9],
+    ]);
+
+    assert!(flat.inverse().is_none());
+    assert!(Mat4::scaling(&Vec3::new(1e6, 1e6, 1e-9))
+        .inverse()
+        .is_none());
+}
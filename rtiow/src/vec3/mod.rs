@@ -1,19 +1,20 @@
-use crate::{
-    interval::Interval,
-    utils::{random, random_in_range},
-};
+use crate::{interval::Interval, utils::Rng};
+use serde::Deserialize;
 use std::ops::{AddAssign, DivAssign, Index, MulAssign, Neg};
 
 mod color;
+mod matrix;
 mod utils;
 
 #[cfg(test)]
 mod test_utils;
 
 pub use color::*;
+pub use matrix::*;
 pub use utils::*;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(from = "[f64; 3]")]
 pub struct Vec3 {
     e: [f64; 3],
 }
@@ -64,23 +65,29 @@ impl Vec3 {
             && (self.e[2].abs() < Self::DELTA_ZERO)
     }
 
-    pub fn random() -> Self {
+    pub fn random(rng: &mut Rng) -> Self {
         Self {
-            e: [random(), random(), random()],
+            e: [rng.random(), rng.random(), rng.random()],
         }
     }
 
-    pub fn random_in_range(range: &Interval) -> Self {
+    pub fn random_in_range(range: &Interval, rng: &mut Rng) -> Self {
         Self {
             e: [
-                random_in_range(range),
-                random_in_range(range),
-                random_in_range(range),
+                rng.random_in_range(range),
+                rng.random_in_range(range),
+                rng.random_in_range(range),
             ],
         }
     }
 }
 
+impl From<[f64; 3]> for Vec3 {
+    fn from(e: [f64; 3]) -> Self {
+        Self { e }
+    }
+}
+
 impl Neg for Vec3 {
     type Output = Self;
 
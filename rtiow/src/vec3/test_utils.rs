@@ -65,3 +65,19 @@ fn test_refract() {
 
     assert_eq!(target, result);
 }
+
+#[test]
+fn test_sample_mappers() {
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-12;
+    let normal = Vec3::new(0_f64, 1_f64, 0_f64);
+
+    assert_eq!(Vec3::zeroes(), sample_unit_disk([0.5, 0.5]));
+    assert!(close(1_f64, sample_unit_disk([0_f64, 0_f64]).length()));
+    assert!(close(1_f64, sample_unit_disk([1_f64, 0.25]).length()));
+
+    for u in [[0_f64, 0_f64], [0.3, 0.9], [0.999, 0.5]] {
+        assert!(sample_unit_disk(u).length() <= 1_f64 + 1e-12);
+        assert!(close(1_f64, sample_unit_vector(u).length()));
+        assert!(dot(&sample_on_hemisphere(&normal, u), &normal) >= 0_f64);
+    }
+}
@@ -1,5 +1,5 @@
 use super::Vec3;
-use crate::{interval::Interval, utils::random_in_range};
+use crate::PI;
 use std::{
     iter::{Iterator, Sum},
     ops::{Add, Div, Mul, Sub},
@@ -136,36 +136,39 @@ pub fn unit_vector(v: &Vec3) -> Vec3 {
     *v / v.length()
 }
 
+/// Maps a uniform 2D sample to a uniform point in the unit disk, keeping
+/// stratified samples stratified (Shirley and Chiu's concentric mapping).
 #[inline]
-pub fn random_in_unit_disk() -> Vec3 {
-    loop {
-        let p = Vec3::new(
-            random_in_range(&Interval::new(-1_f64, 1_f64)),
-            random_in_range(&Interval::new(-1_f64, 1_f64)),
-            0_f64,
-        );
-
-        if p.length_squared() < 1_f64 {
-            return p;
-        }
+pub fn sample_unit_disk(u: [f64; 2]) -> Vec3 {
+    let (a, b) = (2_f64 * u[0] - 1_f64, 2_f64 * u[1] - 1_f64);
+
+    if a == 0_f64 && b == 0_f64 {
+        return Vec3::zeroes();
     }
+
+    let (r, theta) = if a.abs() > b.abs() {
+        (a, PI / 4_f64 * (b / a))
+    } else {
+        (b, PI / 2_f64 - PI / 4_f64 * (a / b))
+    };
+
+    Vec3::new(r * theta.cos(), r * theta.sin(), 0_f64)
 }
 
+/// Maps a uniform 2D sample to a uniform direction.
 #[inline]
-pub fn random_unit_vector() -> Vec3 {
-    loop {
-        let p = Vec3::random_in_range(&Interval::new(-1_f64, 1_f64));
-        let lensq = p.length_squared();
-
-        if 1e-160 < lensq && lensq <= 1_f64 {
-            return p / lensq.sqrt();
-        }
-    }
+pub fn sample_unit_vector(u: [f64; 2]) -> Vec3 {
+    let z = 1_f64 - 2_f64 * u[0];
+    let r = (1_f64 - z * z).max(0_f64).sqrt();
+    let phi = 2_f64 * PI * u[1];
+
+    Vec3::new(r * phi.cos(), r * phi.sin(), z)
 }
 
+/// Maps a uniform 2D sample to a uniform direction on the side of `normal`.
 #[inline]
-pub fn random_on_hemisphere(normal: &Vec3) -> Vec3 {
-    let on_unit_sphere = random_unit_vector();
+pub fn sample_on_hemisphere(normal: &Vec3, u: [f64; 2]) -> Vec3 {
+    let on_unit_sphere = sample_unit_vector(u);
 
     if dot(&on_unit_sphere, normal) > 0_f64 {
         on_unit_sphere
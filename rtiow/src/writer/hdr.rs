@@ -0,0 +1,112 @@
+use super::ImageWriter;
+use crate::{framebuffer::Framebuffer, vec3::Color};
+use anyhow::Result;
+use std::io::{BufWriter, Write};
+
+/// Writes a framebuffer as an uncompressed Radiance RGBE (`.hdr`) image,
+/// keeping the linear radiance unclamped.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HdrWriter;
+
+impl ImageWriter for HdrWriter {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["hdr"]
+    }
+
+    fn high_dynamic_range(&self) -> bool {
+        true
+    }
+
+    fn write(&self, framebuffer: &Framebuffer, output_file: &str) -> Result<()> {
+        let file = super::create_output_file(framebuffer, output_file)?;
+        let mut buf_writer = BufWriter::new(file);
+
+        writeln!(buf_writer, "#?RADIANCE")?;
+        writeln!(buf_writer, "FORMAT=32-bit_rle_rgbe")?;
+        writeln!(buf_writer)?;
+        writeln!(
+            buf_writer,
+            "-Y {} +X {}",
+            framebuffer.height(),
+            framebuffer.width()
+        )?;
+        for color in framebuffer.pixels() {
+            buf_writer.write_all(&to_rgbe(color))?;
+        }
+        buf_writer.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Shared-exponent encoding of a linear color. Negative and non-finite
+/// components cannot be represented and are written as zero.
+pub fn to_rgbe(color: &Color) -> [u8; 4] {
+    let rgb =
+        [color.x(), color.y(), color.z()].map(|e| if e.is_finite() { e.max(0_f64) } else { 0_f64 });
+    let v = rgb[0].max(rgb[1]).max(rgb[2]);
+
+    if v < 1e-32 {
+        return [0; 4];
+    }
+
+    // v = mantissa * 2^exponent with mantissa in [0.5, 1)
+    let mut exponent = v.log2().floor() as i32 + 1;
+
+    if v / 2_f64.powi(exponent) >= 1_f64 {
+        exponent += 1;
+    } else if v / 2_f64.powi(exponent) < 0.5 {
+        exponent -= 1;
+    }
+
+    let exponent = exponent.clamp(-128, 127);
+    let scale = 256_f64 / 2_f64.powi(exponent);
+
+    [
+        (rgb[0] * scale).min(255_f64) as u8,
+        (rgb[1] * scale).min(255_f64) as u8,
+        (rgb[2] * scale).min(255_f64) as u8,
+        (exponent + 128) as u8,
+    ]
+}
+
+#[test]
+fn test_hdr_writer_round_trip() {
+    let path = std::env::temp_dir().join(format!("rtiow_hdr_writer_{}.hdr", std::process::id()));
+    let colors = [
+        Color::new(0_f64, 0_f64, 0_f64),
+        Color::new(0.25, 0.5, 0.75),
+        Color::new(12.5, 3_f64, 0.01),
+        Color::new(1e3, 250_f64, 1e-3),
+        Color::new(-1_f64, 1_f64, 0_f64),
+        Color::new(0.999, 1_f64, 1.001),
+    ];
+    let framebuffer =
+        Framebuffer::from_pixels(3, 2, colors.iter().map(|color| (*color, 1)).collect());
+
+    HdrWriter
+        .write(&framebuffer, &path.to_string_lossy())
+        .expect("hdr is written");
+
+    let image = image::open(&path).expect("hdr is readable").into_rgb32f();
+
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!((3, 2), image.dimensions());
+    for (i, color) in colors.iter().enumerate() {
+        let pixel = image.get_pixel(i as u32 % 3, i as u32 / 3);
+        let v = color.x().max(color.y()).max(color.z());
+
+        for c in 0..3 {
+            // RGBE keeps 8 bits of mantissa relative to the largest component
+            let tolerance = v / 128_f64;
+
+            assert!(
+                (pixel[c] as f64 - color[c].max(0_f64)).abs() <= tolerance,
+                "{:?} read back as {:?}",
+                color,
+                pixel
+            );
+        }
+    }
+}
@@ -1,58 +1,194 @@
-use crate::error::RTError;
+use crate::{error::RTError, framebuffer::Framebuffer, tonemap::ToneMapping};
 use anyhow::{bail, Result};
 use std::{
+    collections::BTreeMap,
+    fmt::Debug,
     fs::{File, OpenOptions},
-    io::{BufWriter, Write},
     path::Path,
+    sync::Arc,
 };
 
-#[derive(Debug)]
-pub struct PpmWriter {
-    pub path: Box<Path>,
-    pub buffer: Box<[[i32; 3]]>,
+mod hdr;
+mod openexr;
+mod pfm;
+mod png;
+mod ppm;
+
+pub use hdr::*;
+pub use openexr::*;
+pub use pfm::*;
+pub use png::*;
+pub use ppm::*;
+
+/// Encodes a framebuffer into an image file.
+pub trait ImageWriter: Send + Sync + Debug {
+    /// Lowercase file extensions, without the leading dot, this writer is
+    /// registered for.
+    fn extensions(&self) -> &'static [&'static str];
+
+    fn write(&self, framebuffer: &Framebuffer, output_file: &str) -> Result<()>;
+
+    /// Whether `write` stores the framebuffer's additional layers in the
+    /// same file.
+    fn writes_layers(&self) -> bool {
+        false
+    }
+
+    /// Whether the format stores linear values as they are, instead of
+    /// clamping and encoding them to 8 or 16 bits.
+    fn high_dynamic_range(&self) -> bool {
+        false
+    }
+}
+
+/// Maps file extensions to the writers that handle them.
+#[derive(Debug, Clone)]
+pub struct WriterRegistry {
+    writers: BTreeMap<String, Arc<dyn ImageWriter>>,
 }
 
-impl PpmWriter {
-    pub fn new(file_path: &str) -> Self {
+impl Default for WriterRegistry {
+    /// A registry with every writer in this module using its default
+    /// settings; `pgm` selects grayscale PPM output.
+    fn default() -> Self {
+        Self::with_tone_mapping(ToneMapping::default())
+    }
+}
+
+impl WriterRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Same as `default`, with `tone_mapping` applied by the 8- and 16-bit
+    /// writers. The floating-point formats always store linear radiance.
+    pub fn with_tone_mapping(tone_mapping: ToneMapping) -> Self {
+        let mut registry = Self::empty();
+        let ppm = PpmWriter::new().tone_mapping(tone_mapping);
+
+        registry.register(Arc::new(PngWriter::new().tone_mapping(tone_mapping)));
+        registry.register(Arc::new(ppm.clone()));
+        registry.register(Arc::new(ppm.grayscale(true)));
+        registry.register(Arc::new(HdrWriter));
+        registry.register(Arc::new(PfmWriter::new()));
+        registry.register(Arc::new(ExrWriter::new()));
+
+        registry
+    }
+
+    pub fn empty() -> Self {
         Self {
-            path: Path::new(file_path).into(),
-            buffer: Box::new([]),
+            writers: BTreeMap::new(),
         }
     }
 
-    pub fn set_buffer(&mut self, buffer: &[[i32; 3]]) {
-        self.buffer = buffer.into()
+    /// Registers `writer` for all of its extensions, replacing the writers
+    /// previously registered for them.
+    pub fn register(&mut self, writer: Arc<dyn ImageWriter>) {
+        for extension in writer.extensions() {
+            self.writers
+                .insert(extension.to_ascii_lowercase(), writer.clone());
+        }
     }
 
-    pub fn write(&self, image_size: [usize; 2]) -> Result<()> {
-        if self.buffer.as_ref().len() == 0 {
-            bail!(RTError::EmptyBufferOnWrite(
-                stringify!("{:?}", self.path.to_string).into(),
-            ));
-        }
+    pub fn get(&self, extension: &str) -> Option<&dyn ImageWriter> {
+        self.writers
+            .get(&extension.to_ascii_lowercase())
+            .map(|writer| writer.as_ref())
+    }
 
-        let file = Self::create_empty_file(&self.path)?;
-        let mut buf_writer = BufWriter::new(file);
+    /// The writer for the extension of `output_file`.
+    pub fn for_path(&self, output_file: &str) -> Result<&dyn ImageWriter> {
+        let extension = Path::new(output_file)
+            .extension()
+            .map(|extension| extension.to_string_lossy());
 
-        writeln!(buf_writer, "P3")?;
-        writeln!(buf_writer, "{} {}", image_size[0], image_size[1])?;
-        writeln!(buf_writer, "255")?;
-        for rgb in self.buffer.as_ref() {
-            writeln!(buf_writer, "{} {} {}", rgb[0], rgb[1], rgb[2])?;
+        match extension
+            .as_deref()
+            .and_then(|extension| self.get(extension))
+        {
+            Some(writer) => Ok(writer),
+            None => bail!(RTError::UnsupportedFormat(output_file.to_string())),
         }
+    }
 
-        Ok(())
+    /// Registered extensions in alphabetical order.
+    pub fn extensions(&self) -> impl Iterator<Item = &str> {
+        self.writers.keys().map(|extension| extension.as_str())
     }
 
-    fn file_exists(path: &Path) -> bool {
-        path.exists()
+    pub fn write(&self, framebuffer: &Framebuffer, output_file: &str) -> Result<()> {
+        self.for_path(output_file)?.write(framebuffer, output_file)
     }
+}
 
-    fn create_empty_file(path: &Path) -> Result<File> {
-        if Self::file_exists(path) {
-            Ok(OpenOptions::new().write(true).create(true).open(path)?)
-        } else {
-            Ok(OpenOptions::new().write(true).create_new(true).open(path)?)
-        }
+/// Encodes `framebuffer` with the default writer for the extension of
+/// `output_file`.
+pub fn write_image(framebuffer: &Framebuffer, output_file: &str) -> Result<()> {
+    WriterRegistry::default().write(framebuffer, output_file)
+}
+
+fn check_not_empty(framebuffer: &Framebuffer, output_file: &str) -> Result<()> {
+    if framebuffer.pixels().is_empty() {
+        bail!(RTError::EmptyBufferOnWrite(output_file.to_string()));
+    }
+
+    Ok(())
+}
+
+fn create_output_file(framebuffer: &Framebuffer, output_file: &str) -> Result<File> {
+    check_not_empty(framebuffer, output_file)?;
+
+    create_empty_file(Path::new(output_file))
+}
+
+fn file_exists(path: &Path) -> bool {
+    path.exists()
+}
+
+fn create_empty_file(path: &Path) -> Result<File> {
+    if file_exists(path) {
+        Ok(OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?)
+    } else {
+        Ok(OpenOptions::new().write(true).create_new(true).open(path)?)
+    }
+}
+
+#[test]
+fn test_writer_registry_picks_writer_by_extension() {
+    let registry = WriterRegistry::default();
+
+    assert_eq!(
+        vec!["exr", "hdr", "pfm", "pgm", "png", "ppm"],
+        registry.extensions().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        ["png"],
+        registry
+            .for_path("images/out.PNG")
+            .expect("png is supported")
+            .extensions()
+    );
+    assert_eq!(
+        ["pgm"],
+        registry
+            .for_path("out.pgm")
+            .expect("pgm is supported")
+            .extensions()
+    );
+
+    for output_file in ["out.bmp", "out"] {
+        let err = registry
+            .for_path(output_file)
+            .expect_err("format is unsupported");
+
+        assert!(matches!(
+            err.downcast_ref::<RTError>(),
+            Some(RTError::UnsupportedFormat(_))
+        ));
     }
 }
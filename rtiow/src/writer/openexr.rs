@@ -0,0 +1,175 @@
+use super::ImageWriter;
+use crate::framebuffer::Framebuffer;
+use anyhow::Result;
+use exr::prelude::{
+    f16, AnyChannel, AnyChannels, Encoding, FlatSamples, Image, Layer, LayerAttributes, SmallVec,
+    WritableImage,
+};
+
+/// Sample type of the color channels of an EXR image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExrPrecision {
+    #[default]
+    Half,
+    Float,
+}
+
+/// Writes a framebuffer as a single-part OpenEXR image with linear `R`, `G`,
+/// `B` and optionally `A` channels. Additional framebuffer layers are stored
+/// as `<layer>.<channel>` channels in 32-bit float, since depth and ids do
+/// not survive half precision.
+#[derive(Debug, Clone, Default)]
+pub struct ExrWriter {
+    pub precision: ExrPrecision,
+    pub alpha: bool,
+}
+
+impl ExrWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn precision(mut self, precision: ExrPrecision) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Adds an `A` channel. Renders have no transparency, so it is opaque.
+    pub fn alpha(mut self, alpha: bool) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    fn color_channel(
+        &self,
+        name: &str,
+        samples: impl Iterator<Item = f64>,
+    ) -> AnyChannel<FlatSamples> {
+        let samples = match self.precision {
+            ExrPrecision::Half => FlatSamples::F16(samples.map(f16::from_f64).collect()),
+            ExrPrecision::Float => FlatSamples::F32(samples.map(|sample| sample as f32).collect()),
+        };
+
+        AnyChannel::new(name, samples)
+    }
+}
+
+impl ImageWriter for ExrWriter {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["exr"]
+    }
+
+    fn high_dynamic_range(&self) -> bool {
+        true
+    }
+
+    fn writes_layers(&self) -> bool {
+        true
+    }
+
+    fn write(&self, framebuffer: &Framebuffer, output_file: &str) -> Result<()> {
+        super::check_not_empty(framebuffer, output_file)?;
+
+        let mut channels = ["R", "G", "B"]
+            .iter()
+            .enumerate()
+            .map(|(c, name)| {
+                self.color_channel(name, framebuffer.pixels().iter().map(|color| color[c]))
+            })
+            .collect::<SmallVec<[AnyChannel<FlatSamples>; 4]>>();
+
+        if self.alpha {
+            channels.push(self.color_channel("A", framebuffer.pixels().iter().map(|_| 1_f64)));
+        }
+        for (name, layer) in framebuffer.layers() {
+            for (c, channel) in layer.channels().iter().enumerate() {
+                channels.push(AnyChannel::new(
+                    format!("{}.{}", name, channel).as_str(),
+                    FlatSamples::F32(layer.channel(c).map(|sample| sample as f32).collect()),
+                ));
+            }
+        }
+
+        let layer = Layer::new(
+            (framebuffer.width(), framebuffer.height()),
+            LayerAttributes::default(),
+            Encoding::FAST_LOSSLESS,
+            AnyChannels::sort(channels),
+        );
+
+        Image::from_layer(layer).write().to_file(output_file)?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_exr_writer_round_trip() {
+    use crate::{framebuffer, vec3::Color};
+    use exr::prelude::{ReadChannels, ReadLayers};
+
+    let path = std::env::temp_dir().join(format!("rtiow_exr_writer_{}.exr", std::process::id()));
+    let colors = [
+        Color::new(0_f64, 0.5, 1_f64),
+        Color::new(12.5, 0.25, 1e3),
+        Color::new(0.1, 0.2, 0.3),
+    ];
+    let mut fb = Framebuffer::from_pixels(3, 1, colors.iter().map(|color| (*color, 1)).collect());
+
+    fb.add_layer(
+        "depth",
+        framebuffer::Layer::new(&["Z"], vec![1_f64, 2.5, 12345.678]),
+    );
+
+    for precision in [ExrPrecision::Half, ExrPrecision::Float] {
+        ExrWriter::new()
+            .precision(precision)
+            .alpha(true)
+            .write(&fb, &path.to_string_lossy())
+            .expect("exr is written");
+
+        let image = exr::prelude::read()
+            .no_deep_data()
+            .largest_resolution_level()
+            .all_channels()
+            .first_valid_layer()
+            .all_attributes()
+            .from_file(&path)
+            .expect("exr is readable");
+        let channels = &image.layer_data.channel_data.list;
+        let names = channels
+            .iter()
+            .map(|channel| channel.name.to_string())
+            .collect::<Vec<_>>();
+        let channel = |name: &str| {
+            channels
+                .iter()
+                .find(|channel| channel.name.to_string() == name)
+                .map(|channel| channel.sample_data.values_as_f32().collect::<Vec<f32>>())
+                .expect("channel exists")
+        };
+        let tolerance = match precision {
+            ExrPrecision::Half => 1e-3,
+            ExrPrecision::Float => 0_f32,
+        };
+
+        assert_eq!(vec!["A", "B", "G", "R", "depth.Z"], names);
+        assert_eq!(vec![1_f32, 2.5, 12345.678], channel("depth.Z"));
+        assert_eq!(vec![1_f32; 3], channel("A"));
+        for (c, name) in ["R", "G", "B"].iter().enumerate() {
+            for (sample, color) in channel(name).iter().zip(colors.iter()) {
+                let expected = color[c] as f32;
+
+                assert!(
+                    (sample - expected).abs() <= tolerance * expected.max(1_f32),
+                    "{}: {} != {}",
+                    name,
+                    sample,
+                    expected
+                );
+            }
+        }
+    }
+
+    std::fs::remove_file(&path).ok();
+}
@@ -0,0 +1,117 @@
+use super::ImageWriter;
+use crate::{framebuffer::Framebuffer, vec3};
+use anyhow::Result;
+use std::io::{BufWriter, Write};
+
+/// Writes a framebuffer as a little-endian Portable Float Map (`.pfm`) with
+/// unclamped linear samples, or in grayscale mode a `Pf` map of the
+/// luminance.
+#[derive(Debug, Clone, Default)]
+pub struct PfmWriter {
+    pub grayscale: bool,
+}
+
+impl PfmWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn grayscale(mut self, grayscale: bool) -> Self {
+        self.grayscale = grayscale;
+        self
+    }
+}
+
+impl ImageWriter for PfmWriter {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["pfm"]
+    }
+
+    fn high_dynamic_range(&self) -> bool {
+        true
+    }
+
+    fn write(&self, framebuffer: &Framebuffer, output_file: &str) -> Result<()> {
+        let file = super::create_output_file(framebuffer, output_file)?;
+        let mut buf_writer = BufWriter::new(file);
+
+        writeln!(buf_writer, "{}", if self.grayscale { "Pf" } else { "PF" })?;
+        writeln!(
+            buf_writer,
+            "{} {}",
+            framebuffer.width(),
+            framebuffer.height()
+        )?;
+        // A negative scale marks the samples as little-endian
+        writeln!(buf_writer, "-1.0")?;
+        // PFM stores the bottom row first
+        for row in framebuffer.pixels().chunks(framebuffer.width()).rev() {
+            for color in row {
+                if self.grayscale {
+                    buf_writer.write_all(&(vec3::luminance(color) as f32).to_le_bytes())?;
+                } else {
+                    for c in 0..3 {
+                        buf_writer.write_all(&(color[c] as f32).to_le_bytes())?;
+                    }
+                }
+            }
+        }
+        buf_writer.flush()?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_pfm_writer_round_trip() {
+    use crate::vec3::Color;
+
+    let path = std::env::temp_dir().join(format!("rtiow_pfm_writer_{}.pfm", std::process::id()));
+    let colors = [
+        Color::new(0_f64, 0.5, 1_f64),
+        Color::new(12.5, -0.25, 1e3),
+        Color::new(0.1, 0.2, 0.3),
+        Color::new(4_f64, 5_f64, 6_f64),
+    ];
+    let framebuffer =
+        Framebuffer::from_pixels(2, 2, colors.iter().map(|color| (*color, 1)).collect());
+
+    for grayscale in [false, true] {
+        PfmWriter::new()
+            .grayscale(grayscale)
+            .write(&framebuffer, &path.to_string_lossy())
+            .expect("pfm is written");
+
+        let bytes = std::fs::read(&path).expect("pfm is readable");
+        let header = if grayscale {
+            "Pf\n2 2\n-1.0\n"
+        } else {
+            "PF\n2 2\n-1.0\n"
+        };
+        let channels = if grayscale { 1 } else { 3 };
+
+        assert!(bytes.starts_with(header.as_bytes()));
+
+        let samples = bytes[header.len()..]
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect::<Vec<f32>>();
+
+        assert_eq!(4 * channels, samples.len());
+        for (i, color) in colors.iter().enumerate() {
+            // Rows are flipped: pixel (x, y) is stored at row (height - 1 - y)
+            let (x, y) = (i % 2, i / 2);
+            let stored = &samples[((1 - y) * 2 + x) * channels..][..channels];
+
+            if grayscale {
+                assert_eq!(vec3::luminance(color) as f32, stored[0]);
+            } else {
+                for c in 0..3 {
+                    assert_eq!(color[c] as f32, stored[c]);
+                }
+            }
+        }
+    }
+
+    std::fs::remove_file(&path).ok();
+}
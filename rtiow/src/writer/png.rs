@@ -0,0 +1,72 @@
+use super::ImageWriter;
+use crate::{framebuffer::Framebuffer, tonemap::ToneMapping, vec3};
+use anyhow::Result;
+use image::{ImageBuffer, Rgb, RgbImage};
+
+/// Writes a framebuffer as a tone-mapped, sRGB-encoded 8-bit PNG image.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PngWriter {
+    pub tone_mapping: ToneMapping,
+}
+
+impl PngWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn tone_mapping(mut self, tone_mapping: ToneMapping) -> Self {
+        self.tone_mapping = tone_mapping;
+        self
+    }
+}
+
+impl ImageWriter for PngWriter {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["png"]
+    }
+
+    fn write(&self, framebuffer: &Framebuffer, output_file: &str) -> Result<()> {
+        super::check_not_empty(framebuffer, output_file)?;
+
+        let buffer: RgbImage = ImageBuffer::from_fn(
+            framebuffer.width() as u32,
+            framebuffer.height() as u32,
+            |x, y| {
+                let color = framebuffer.pixel(x as usize, y as usize);
+                let rgb = vec3::write_color(&self.tone_mapping.apply(&color));
+
+                Rgb([rgb[0] as u8, rgb[1] as u8, rgb[2] as u8])
+            },
+        );
+
+        buffer.save(output_file)?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_png_writer_round_trip() {
+    use crate::vec3::Color;
+
+    let path = std::env::temp_dir().join(format!("rtiow_png_writer_{}.png", std::process::id()));
+    let framebuffer = Framebuffer::from_pixels(
+        2,
+        1,
+        vec![
+            (Color::new(1_f64, 0.25, 0_f64), 4),
+            (Color::new(0_f64, 0_f64, 0.01), 4),
+        ],
+    );
+
+    PngWriter::new()
+        .write(&framebuffer, &path.to_string_lossy())
+        .expect("png is written");
+
+    let image = image::open(&path).expect("png is readable").to_rgb8();
+
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!([255, 137, 0], image.get_pixel(0, 0).0);
+    assert_eq!([0, 0, 25], image.get_pixel(1, 0).0);
+}
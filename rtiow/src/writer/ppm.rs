@@ -0,0 +1,204 @@
+use super::ImageWriter;
+use crate::{
+    framebuffer::Framebuffer,
+    tonemap::ToneMapping,
+    vec3::{self, Color},
+};
+use anyhow::Result;
+use std::io::{BufWriter, Write};
+
+/// How the samples of a PPM/PGM file are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PpmEncoding {
+    /// Whitespace separated decimal samples (`P3`/`P2`).
+    #[default]
+    Ascii,
+    /// Raw big-endian samples (`P6`/`P5`).
+    Binary,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BitDepth {
+    #[default]
+    Eight,
+    Sixteen,
+}
+
+/// Writes a framebuffer as a tone-mapped Netpbm pixmap (PPM) or, in
+/// grayscale mode, a graymap (PGM) of the framebuffer's luminance.
+#[derive(Debug, Clone, Default)]
+pub struct PpmWriter {
+    pub encoding: PpmEncoding,
+    pub bit_depth: BitDepth,
+    pub grayscale: bool,
+    pub tone_mapping: ToneMapping,
+}
+
+impl PpmWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn encoding(mut self, encoding: PpmEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn bit_depth(mut self, bit_depth: BitDepth) -> Self {
+        self.bit_depth = bit_depth;
+        self
+    }
+
+    pub fn grayscale(mut self, grayscale: bool) -> Self {
+        self.grayscale = grayscale;
+        self
+    }
+
+    pub fn tone_mapping(mut self, tone_mapping: ToneMapping) -> Self {
+        self.tone_mapping = tone_mapping;
+        self
+    }
+
+    pub fn magic_number(&self) -> &'static str {
+        match (self.encoding, self.grayscale) {
+            (PpmEncoding::Ascii, false) => "P3",
+            (PpmEncoding::Ascii, true) => "P2",
+            (PpmEncoding::Binary, false) => "P6",
+            (PpmEncoding::Binary, true) => "P5",
+        }
+    }
+
+    /// Quantized samples of one pixel; only the first one is used for
+    /// graymaps.
+    fn samples(&self, color: &Color) -> [u16; 3] {
+        let color = self.tone_mapping.apply(color);
+        let color = if self.grayscale {
+            let y = vec3::luminance(&color);
+
+            Color::new(y, y, y)
+        } else {
+            color
+        };
+
+        match self.bit_depth {
+            BitDepth::Eight => vec3::write_color(&color).map(|sample| sample as u16),
+            BitDepth::Sixteen => vec3::write_color_16(&color),
+        }
+    }
+}
+
+impl ImageWriter for PpmWriter {
+    fn extensions(&self) -> &'static [&'static str] {
+        if self.grayscale {
+            &["pgm"]
+        } else {
+            &["ppm"]
+        }
+    }
+
+    fn write(&self, framebuffer: &Framebuffer, output_file: &str) -> Result<()> {
+        let file = super::create_output_file(framebuffer, output_file)?;
+        let mut buf_writer = BufWriter::new(file);
+        let max_value = match self.bit_depth {
+            BitDepth::Eight => 255,
+            BitDepth::Sixteen => 65535,
+        };
+
+        writeln!(buf_writer, "{}", self.magic_number())?;
+        writeln!(
+            buf_writer,
+            "{} {}",
+            framebuffer.width(),
+            framebuffer.height()
+        )?;
+        writeln!(buf_writer, "{}", max_value)?;
+        for color in framebuffer.pixels() {
+            let samples = self.samples(color);
+            let samples = if self.grayscale {
+                &samples[..1]
+            } else {
+                &samples[..]
+            };
+
+            match (self.encoding, self.bit_depth) {
+                (PpmEncoding::Ascii, _) => {
+                    let line = samples
+                        .iter()
+                        .map(|sample| sample.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" ");
+
+                    writeln!(buf_writer, "{}", line)?;
+                }
+                (PpmEncoding::Binary, BitDepth::Eight) => {
+                    for sample in samples {
+                        buf_writer.write_all(&[*sample as u8])?;
+                    }
+                }
+                (PpmEncoding::Binary, BitDepth::Sixteen) => {
+                    for sample in samples {
+                        buf_writer.write_all(&sample.to_be_bytes())?;
+                    }
+                }
+            }
+        }
+        buf_writer.flush()?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_ppm_writer_encodings() {
+    use crate::tonemap::ToneMapOperator;
+
+    let path = std::env::temp_dir().join(format!("rtiow_ppm_writer_{}.ppm", std::process::id()));
+    let framebuffer = Framebuffer::from_pixels(
+        2,
+        1,
+        vec![
+            (Color::new(1_f64, 0.25, 0_f64), 1),
+            (Color::new(0_f64, 0_f64, 1_f64), 1),
+        ],
+    );
+    let cases: [(PpmWriter, &[u8]); 5] = [
+        (PpmWriter::new(), b"P3\n2 1\n255\n255 137 0\n0 0 255\n"),
+        (
+            PpmWriter::new().encoding(PpmEncoding::Binary),
+            b"P6\n2 1\n255\n\xff\x89\x00\x00\x00\xff",
+        ),
+        (
+            PpmWriter::new()
+                .encoding(PpmEncoding::Binary)
+                .bit_depth(BitDepth::Sixteen),
+            b"P6\n2 1\n65535\n\xff\xff\x89\x7f\x00\x00\x00\x00\x00\x00\xff\xff",
+        ),
+        (
+            PpmWriter::new()
+                .bit_depth(BitDepth::Sixteen)
+                .grayscale(true),
+            b"P2\n2 1\n65535\n43168\n19522\n",
+        ),
+        (
+            PpmWriter::new().tone_mapping(ToneMapping::new(ToneMapOperator::Reinhard)),
+            b"P3\n2 1\n255\n221 118 0\n0 0 248\n",
+        ),
+    ];
+
+    for (writer, expected) in cases.iter() {
+        writer
+            .write(&framebuffer, &path.to_string_lossy())
+            .expect("ppm is written");
+
+        let written = std::fs::read(&path).expect("ppm is readable");
+
+        assert_eq!(
+            String::from_utf8_lossy(expected),
+            String::from_utf8_lossy(&written),
+            "{}",
+            writer.magic_number()
+        );
+    }
+
+    std::fs::remove_file(&path).ok();
+}
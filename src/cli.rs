@@ -0,0 +1,431 @@
+use crate::scenes::{self, SCENES};
+use anyhow::{bail, Context, Result};
+use clap::{Parser, ValueEnum};
+use rtiow::{
+    camera::{AdaptiveSampling, Aov},
+    denoise::Denoiser,
+    error::RTError,
+    framebuffer::Framebuffer,
+    sampler::SamplerKind,
+    scene::Scene,
+    tonemap::{ToneMapOperator, ToneMapping},
+    utils::{self, Rng},
+    writer::WriterRegistry,
+};
+use std::{fs, path::PathBuf};
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Ray Tracing in One Weekend renderer")]
+pub struct Cli {
+    /// Built-in scene to render (see --list)
+    #[arg(default_value = "perlin_spheres", conflicts_with = "file")]
+    pub scene: String,
+
+    /// Render a TOML scene file instead of a built-in scene
+    #[arg(short, long, value_name = "PATH")]
+    pub file: Option<PathBuf>,
+
+    /// List the built-in scenes and exit
+    #[arg(short, long)]
+    pub list: bool,
+
+    /// Image width in pixels
+    #[arg(short, long, value_parser = clap::value_parser!(i32).range(1..))]
+    pub width: Option<i32>,
+
+    /// Image aspect ratio (width / height)
+    #[arg(short, long, value_parser = parse_positive)]
+    pub aspect_ratio: Option<f64>,
+
+    /// Samples per pixel
+    #[arg(short, long, alias = "spp", value_parser = clap::value_parser!(i32).range(1..))]
+    pub samples_per_pixel: Option<i32>,
+
+    /// Maximum number of ray bounces
+    #[arg(short = 'd', long, value_parser = clap::value_parser!(i32).range(1..))]
+    pub max_depth: Option<i32>,
+
+    /// Output image path [default: the scene's own path under images/]
+    #[arg(short, long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Output image format, given as a file extension such as png, ppm,
+    /// pgm, hdr, pfm or exr. A known output extension must name the same
+    /// format [default: from the output extension, else png]
+    #[arg(long, value_parser = parse_format)]
+    pub format: Option<String>,
+
+    /// Number of render threads [default: one per logical core]
+    #[arg(short = 'j', long, value_parser = clap::value_parser!(u16).range(1..))]
+    pub threads: Option<u16>,
+
+    /// Seed for the built-in scene's random layout and for the sampler; the
+    /// same seed reproduces the same image regardless of the number of
+    /// threads
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Sample sequence for pixel, lens, time and scattering samples
+    /// [default: the scene's sampler, else independent]
+    #[arg(long, value_enum)]
+    pub sampler: Option<SamplerArg>,
+
+    /// Sample adaptively, stopping pixels whose relative error drops below
+    /// THRESHOLD; --samples-per-pixel becomes the maximum
+    #[arg(long, value_name = "THRESHOLD", value_parser = parse_positive)]
+    pub adaptive: Option<f64>,
+
+    /// Also write a heatmap of the samples spent per pixel to
+    /// <output>.heatmap.<ext>
+    #[arg(long)]
+    pub heatmap: bool,
+
+    /// Tone-mapping curve applied before writing 8- and 16-bit images
+    #[arg(long, value_enum, default_value_t = ToneMap::None)]
+    pub tone_map: ToneMap,
+
+    /// Luminance mapped to white by the reinhard-extended curve
+    #[arg(long, value_parser = parse_positive)]
+    pub white_point: Option<f64>,
+
+    /// Exposure compensation in stops, applied before tone mapping
+    #[arg(long, default_value_t = 0_f64, allow_negative_numbers = true)]
+    pub exposure: f64,
+
+    /// Auxiliary outputs to record, stored as layers in EXR files and as
+    /// separate <output>.<aov>.<ext> images otherwise; 8- and 16-bit images
+    /// show depth, normals and material ids remapped to [0, 1]
+    #[arg(long = "aov", value_enum, value_delimiter = ',')]
+    pub aovs: Vec<AovArg>,
+
+    /// Filter the render with the built-in denoiser, guided by its depth,
+    /// normal and albedo
+    #[arg(long)]
+    pub denoise: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AovArg {
+    Depth,
+    Normal,
+    Albedo,
+    Uv,
+    MaterialId,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SamplerArg {
+    Independent,
+    Stratified,
+    Halton,
+    Sobol,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ToneMap {
+    None,
+    Reinhard,
+    ReinhardExtended,
+    Aces,
+}
+
+#[derive(Debug)]
+enum SceneSource {
+    Builtin(fn(&mut Rng) -> Result<Scene>),
+    /// A scene file, loaded and validated while building the job.
+    File(Box<Scene>),
+}
+
+/// A fully validated render request. Everything that can be rejected without
+/// rendering is checked while building it.
+#[derive(Debug)]
+pub struct RenderJob {
+    source: SceneSource,
+    width: Option<i32>,
+    aspect_ratio: Option<f64>,
+    samples_per_pixel: Option<i32>,
+    max_depth: Option<i32>,
+    output: PathBuf,
+    format: String,
+    threads: Option<u16>,
+    seed: Option<u64>,
+    sampler: Option<SamplerKind>,
+    adaptive: Option<f64>,
+    heatmap: bool,
+    tone_mapping: ToneMapping,
+    aovs: Vec<Aov>,
+    denoise: bool,
+}
+
+impl Cli {
+    const DEFAULT_WHITE_POINT: f64 = 4_f64;
+
+    pub fn print_scenes() {
+        let width = SCENES
+            .iter()
+            .map(|scene| scene.name.len())
+            .max()
+            .unwrap_or(0);
+
+        for scene in SCENES.iter() {
+            println!(
+                "{:width$}  {}",
+                scene.name,
+                scene.description,
+                width = width
+            );
+        }
+    }
+
+    pub fn job(&self) -> Result<RenderJob> {
+        let (source, default_output) = match &self.file {
+            Some(path) => {
+                if !path.is_file() {
+                    bail!("scene file {:?} does not exist", path);
+                }
+
+                let scene = Scene::load(path.to_string_lossy().as_ref())?;
+                let stem = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "scene".into());
+
+                (
+                    SceneSource::File(Box::new(scene)),
+                    PathBuf::from(format!("images/{}.png", stem)),
+                )
+            }
+            None => match scenes::find(&self.scene) {
+                Some(scene) => (
+                    SceneSource::Builtin(scene.build),
+                    PathBuf::from(scene.output),
+                ),
+                None => bail!(
+                    "unknown scene {:?}; run with --list to see the available scenes",
+                    self.scene
+                ),
+            },
+        };
+        let registry = WriterRegistry::default();
+        let (output, format) = match (&self.output, &self.format) {
+            (Some(output), Some(format)) => {
+                // An unknown extension is kept; a known one must agree
+                if let Ok(writer) = registry.for_path(&output.to_string_lossy()) {
+                    if !writer.extensions().contains(&format.as_str()) {
+                        bail!(
+                            "output {:?} does not match --format {}; use a .{} extension",
+                            output,
+                            format,
+                            format
+                        );
+                    }
+                }
+
+                (output.clone(), format.clone())
+            }
+            (Some(output), None) => match registry.for_path(&output.to_string_lossy()) {
+                Ok(writer) => (output.clone(), writer.extensions()[0].to_string()),
+                Err(e) => bail!("{}; pass --format", e),
+            },
+            (None, format) => {
+                let format = format.clone().unwrap_or_else(|| "png".into());
+
+                (default_output.with_extension(&format), format)
+            }
+        };
+
+        let operator = match (self.tone_map, self.white_point) {
+            (ToneMap::None, None) => ToneMapOperator::None,
+            (ToneMap::Reinhard, None) => ToneMapOperator::Reinhard,
+            (ToneMap::ReinhardExtended, white) => ToneMapOperator::ReinhardExtended {
+                white: white.unwrap_or(Self::DEFAULT_WHITE_POINT),
+            },
+            (ToneMap::Aces, None) => ToneMapOperator::Aces,
+            (_, Some(_)) => bail!("--white-point requires --tone-map reinhard-extended"),
+        };
+
+        if !self.exposure.is_finite() {
+            bail!("--exposure must be a finite number of stops");
+        }
+
+        Ok(RenderJob {
+            source,
+            width: self.width,
+            aspect_ratio: self.aspect_ratio,
+            samples_per_pixel: self.samples_per_pixel,
+            max_depth: self.max_depth,
+            output,
+            format,
+            threads: self.threads,
+            seed: self.seed,
+            sampler: self.sampler.as_ref().map(|sampler| sampler.into()),
+            adaptive: self.adaptive,
+            heatmap: self.heatmap,
+            tone_mapping: ToneMapping::new(operator).exposure(self.exposure),
+            aovs: self.aovs.iter().map(|aov| aov.into()).collect(),
+            denoise: self.denoise,
+        })
+    }
+}
+
+impl RenderJob {
+    pub fn run(&self) -> Result<()> {
+        if let Some(threads) = self.threads {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads as usize)
+                .build_global()?;
+        }
+
+        let mut scene = match &self.source {
+            SceneSource::Builtin(build) => {
+                build(&mut Rng::new(self.seed.unwrap_or_else(utils::random_seed)))?
+            }
+            SceneSource::File(scene) => scene.as_ref().clone(),
+        };
+
+        if let Some(width) = self.width {
+            scene.camera = scene.camera.image_width(width);
+        }
+        if let Some(aspect_ratio) = self.aspect_ratio {
+            scene.camera = scene.camera.aspect_ratio(aspect_ratio);
+        }
+        if let Some(samples_per_pixel) = self.samples_per_pixel {
+            scene.camera = scene.camera.samples_per_pixel(samples_per_pixel);
+        }
+        if let Some(max_depth) = self.max_depth {
+            scene.camera = scene.camera.max_depth(max_depth);
+        }
+        if !self.aovs.is_empty() {
+            scene.camera = scene.camera.aovs(&self.aovs);
+        }
+        if self.denoise {
+            scene.camera = scene.camera.denoise(Some(Denoiser::default()));
+        }
+        if let Some(seed) = self.seed {
+            scene.camera = scene.camera.seed(seed);
+        }
+        if let Some(sampler) = self.sampler {
+            scene.camera = scene.camera.sampler(sampler);
+        }
+        if let Some(threshold) = self.adaptive {
+            scene.camera = scene
+                .camera
+                .adaptive(Some(AdaptiveSampling::new(threshold)));
+        }
+
+        if let Some(parent) = self.output.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("failed to create {:?}", parent))?;
+        }
+
+        let output = self.output.to_string_lossy();
+        let camera = scene.camera()?;
+
+        let registry = WriterRegistry::with_tone_mapping(self.tone_mapping);
+        let writer = registry
+            .get(&self.format)
+            .ok_or_else(|| RTError::UnsupportedFormat(self.format.clone()))?;
+        let framebuffer = camera.render(&scene.world);
+
+        writer
+            .write(&framebuffer, &output)
+            .with_context(|| format!("failed to write {:?}", output))?;
+
+        if !writer.writes_layers() {
+            self.write_layers(&framebuffer)?;
+        }
+        if self.heatmap {
+            let output = self.sibling_output("heatmap");
+            let output = output.to_string_lossy();
+
+            WriterRegistry::default()
+                .write(&framebuffer.sample_heatmap(), &output)
+                .with_context(|| format!("failed to write {:?}", output))?;
+        }
+
+        eprintln!("\nDone!");
+
+        Ok(())
+    }
+
+    /// Writes every framebuffer layer next to the output as its own image,
+    /// without tone mapping. 8- and 16-bit formats get AOVs remapped to a
+    /// displayable range.
+    fn write_layers(&self, framebuffer: &Framebuffer) -> Result<()> {
+        let registry = WriterRegistry::default();
+        let writer = registry
+            .get(&self.format)
+            .ok_or_else(|| RTError::UnsupportedFormat(self.format.clone()))?;
+
+        for (name, _) in framebuffer.layers() {
+            let output = self.sibling_output(name);
+            let output = output.to_string_lossy();
+            let image = match Aov::ALL.iter().find(|aov| aov.name() == name) {
+                Some(aov) if !writer.high_dynamic_range() => aov.display_image(framebuffer),
+                _ => framebuffer.layer_image(name),
+            };
+
+            if let Some(image) = image {
+                writer
+                    .write(&image, &output)
+                    .with_context(|| format!("failed to write {:?}", output))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `<output stem>.<name>.<format>` next to the output.
+    fn sibling_output(&self, name: &str) -> PathBuf {
+        let stem = self
+            .output
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        self.output
+            .with_file_name(format!("{}.{}.{}", stem, name, self.format))
+    }
+}
+
+impl From<&AovArg> for Aov {
+    fn from(aov: &AovArg) -> Self {
+        match aov {
+            AovArg::Depth => Self::Depth,
+            AovArg::Normal => Self::Normal,
+            AovArg::Albedo => Self::Albedo,
+            AovArg::Uv => Self::Uv,
+            AovArg::MaterialId => Self::MaterialId,
+        }
+    }
+}
+
+impl From<&SamplerArg> for SamplerKind {
+    fn from(sampler: &SamplerArg) -> Self {
+        match sampler {
+            SamplerArg::Independent => Self::Independent,
+            SamplerArg::Stratified => Self::Stratified,
+            SamplerArg::Halton => Self::Halton,
+            SamplerArg::Sobol => Self::Sobol,
+        }
+    }
+}
+
+fn parse_positive(arg: &str) -> Result<f64, String> {
+    match arg.parse::<f64>() {
+        Ok(value) if value > 0_f64 && value.is_finite() => Ok(value),
+        Ok(_) => Err("must be a positive number".into()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn parse_format(arg: &str) -> Result<String, String> {
+    let registry = WriterRegistry::default();
+
+    match registry.get(arg) {
+        Some(_) => Ok(arg.to_ascii_lowercase()),
+        None => Err(format!(
+            "unsupported format; expected one of {}",
+            registry.extensions().collect::<Vec<_>>().join(", ")
+        )),
+    }
+}
@@ -1,14 +1,38 @@
+mod cli;
 mod scenes;
 
-use anyhow::{bail, Result};
-use scenes::*;
+use clap::Parser;
+use cli::Cli;
+use std::process::ExitCode;
 
-fn main() -> Result<()> {
-    match 4 {
-        1 => bouncing_spheres(),
-        2 => checkered_spheres(),
-        3 => earth(),
-        4 => perlin_spheres(),
-        val => bail!("Option is not available. ({})", val),
+/// Exit code for arguments rejected before rendering, matching clap's own
+/// usage errors.
+const EXIT_USAGE: u8 = 2;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    if cli.list {
+        Cli::print_scenes();
+
+        return ExitCode::SUCCESS;
+    }
+
+    let job = match cli.job() {
+        Ok(job) => job,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+
+    match job.run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("render failed: {:#}", e);
+
+            ExitCode::FAILURE
+        }
     }
 }
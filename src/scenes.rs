@@ -1,14 +1,84 @@
 use anyhow::Result;
 use rtiow::{
-    camera::Camera,
-    hittable::{Dielectric, HittableList, Lambertian, Metal, Sphere},
+    background::SolidBackground,
+    camera::CameraBuilder,
+    hittable::{
+        self, BvhNode, BvhOptions, ConstantMedium, Dielectric, DiffuseLight, Hittable,
+        HittableList, Lambertian, LinearBvh, Metal, Quad, RotateY, Sphere, Translate,
+    },
     interval::Interval,
+    scene::Scene,
     texture::{CheckerTexture, ImageTexture, NoiseTexture, Texture},
+    utils::Rng,
     vec3::{Color, Point3, Vec3},
 };
 use std::sync::Arc;
 
-pub fn bouncing_spheres() -> Result<()> {
+#[derive(Debug, Clone, Copy)]
+pub struct BuiltinScene {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub output: &'static str,
+    /// Builds the scene, drawing any random layout from the generator.
+    pub build: fn(&mut Rng) -> Result<Scene>,
+}
+
+pub const SCENES: [BuiltinScene; 8] = [
+    BuiltinScene {
+        name: "bouncing_spheres",
+        description: "Final scene of Ray Tracing in One Weekend with motion blur",
+        output: "images/checkered_ground.png",
+        build: bouncing_spheres,
+    },
+    BuiltinScene {
+        name: "checkered_spheres",
+        description: "Two spheres with a checker texture",
+        output: "images/checkered_spheres.png",
+        build: checkered_spheres,
+    },
+    BuiltinScene {
+        name: "earth",
+        description: "Image-textured globe (needs earthmap.jpg)",
+        output: "images/earthmap.png",
+        build: earth,
+    },
+    BuiltinScene {
+        name: "perlin_spheres",
+        description: "Perlin noise textured spheres",
+        output: "images/hashed_random_texture.png",
+        build: perlin_spheres,
+    },
+    BuiltinScene {
+        name: "quads",
+        description: "Five colored quads",
+        output: "images/quads.png",
+        build: quads,
+    },
+    BuiltinScene {
+        name: "simple_light",
+        description: "Noise textured spheres lit by an area light",
+        output: "images/simple_light.png",
+        build: simple_light,
+    },
+    BuiltinScene {
+        name: "cornell_box",
+        description: "Cornell box with two rotated boxes",
+        output: "images/cornell_box.png",
+        build: cornell_box,
+    },
+    BuiltinScene {
+        name: "final_scene",
+        description: "Final scene of Ray Tracing: The Next Week (needs earthmap.jpg)",
+        output: "images/final_scene.png",
+        build: final_scene,
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static BuiltinScene> {
+    SCENES.iter().find(|scene| scene.name == name)
+}
+
+pub fn bouncing_spheres(rng: &mut Rng) -> Result<Scene> {
     let mut world = HittableList::new();
 
     let sphere_range = Point3::new(4_f64, 0.2, 0_f64);
@@ -23,30 +93,26 @@ pub fn bouncing_spheres() -> Result<()> {
     for ab in 0..484 {
         let a = (ab / 22 - 11) as f64;
         let b = (ab % 22 - 11) as f64;
-        let choose_mat = rtiow::utils::random();
-        let center_1 = Point3::new(
-            a + 0.9 * rtiow::utils::random(),
-            0.2,
-            b + 0.9 * rtiow::utils::random(),
-        );
+        let choose_mat = rng.random();
+        let center_1 = Point3::new(a + 0.9 * rng.random(), 0.2, b + 0.9 * rng.random());
         let center_2 = center_1
             + Vec3::new(
                 0_f64,
-                rtiow::utils::random_in_range(&Interval::new(0_f64, 0.5)),
+                rng.random_in_range(&Interval::new(0_f64, 0.5)),
                 0_f64,
             );
 
         if (center_1 - sphere_range).length() > 0.9 {
             if choose_mat < 0.8 {
-                let albedo = Color::random() * Color::random();
+                let albedo = Color::random(rng) * Color::random(rng);
                 let material = Arc::new(Lambertian::new(albedo));
 
                 world.add(Arc::new(Sphere::new_moving(
                     center_1, center_2, 0.2, material,
                 )));
             } else if choose_mat < 0.95 {
-                let albedo = Color::random_in_range(&Interval::new(0.5, 1_f64));
-                let fuzz = rtiow::utils::random_in_range(&Interval::new(0_f64, 0.5));
+                let albedo = Color::random_in_range(&Interval::new(0.5, 1_f64), rng);
+                let fuzz = rng.random_in_range(&Interval::new(0_f64, 0.5));
                 let material = Arc::new(Metal::new(albedo, fuzz));
 
                 world.add(Arc::new(Sphere::new_moving(
@@ -82,25 +148,21 @@ pub fn bouncing_spheres() -> Result<()> {
         material3,
     )));
 
-    let cam = Camera::new(
-        16_f64 / 9_f64,
-        1200,
-        500,
-        50,
-        20_f64,
-        &Point3::new(13_f64, 2_f64, 3_f64),
-        &Point3::zeroes(),
-        &Vec3::new(0_f64, 1_f64, 0_f64),
-        0.6,
-        1e1,
-    );
-
-    cam.render_png(&world, "images/checkered_ground.png")?;
+    let world = LinearBvh::from(&world);
+    let camera = CameraBuilder::new()
+        .aspect_ratio(16_f64 / 9_f64)
+        .image_width(1200)
+        .samples_per_pixel(500)
+        .max_depth(50)
+        .vfov(20_f64)
+        .look_from(Point3::new(13_f64, 2_f64, 3_f64))
+        .look_at(Point3::zeroes())
+        .defocus_angle(0.6);
 
-    Ok(())
+    Ok(Scene::new(camera, HittableList::from(Arc::new(world))))
 }
 
-pub fn checkered_spheres() -> Result<()> {
+pub fn checkered_spheres(_rng: &mut Rng) -> Result<Scene> {
     let mut world = HittableList::new();
 
     let checker: Arc<dyn Texture> = Arc::new(CheckerTexture::new(
@@ -120,71 +182,356 @@ pub fn checkered_spheres() -> Result<()> {
         Arc::new(Lambertian::from(checker)),
     )));
 
-    let cam = Camera::new(
-        16_f64 / 9_f64,
-        400,
-        100,
-        50,
-        20_f64,
-        &Point3::new(13_f64, 2_f64, 3_f64),
-        &Point3::zeroes(),
-        &Vec3::new(0_f64, 1_f64, 0_f64),
-        0_f64,
-        1e1,
-    );
-
-    cam.render_png(&world, "images/checkered_spheres.png")?;
+    let camera = CameraBuilder::new()
+        .aspect_ratio(16_f64 / 9_f64)
+        .image_width(400)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .vfov(20_f64)
+        .look_from(Point3::new(13_f64, 2_f64, 3_f64))
+        .look_at(Point3::zeroes());
 
-    Ok(())
+    Ok(Scene::new(camera, world))
 }
 
-pub fn earth() -> Result<()> {
+pub fn earth(_rng: &mut Rng) -> Result<Scene> {
     let earth_texture: Arc<dyn Texture> = Arc::new(ImageTexture::new("earthmap.jpg")?);
     let earth_surface = Arc::new(Lambertian::from(earth_texture));
     let globe = Sphere::new(Point3::zeroes(), 2_f64, earth_surface);
-    let cam = Camera::new(
-        16_f64 / 9_f64,
-        400,
-        100,
-        50,
-        20_f64,
-        &Point3::new(0_f64, 0_f64, 12_f64),
-        &Point3::zeroes(),
-        &Vec3::new(0_f64, 1_f64, 0_f64),
-        0_f64,
-        1e1,
-    );
+    let camera = CameraBuilder::new()
+        .aspect_ratio(16_f64 / 9_f64)
+        .image_width(400)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .vfov(20_f64)
+        .look_from(Point3::new(0_f64, 0_f64, 12_f64))
+        .look_at(Point3::zeroes());
 
-    cam.render_png(&globe, "images/earthmap.png")?;
+    Ok(Scene::new(camera, HittableList::from(Arc::new(globe))))
+}
 
-    Ok(())
+pub fn perlin_spheres(rng: &mut Rng) -> Result<Scene> {
+    let mut world = HittableList::new();
+    let camera = CameraBuilder::new()
+        .aspect_ratio(16_f64 / 9_f64)
+        .image_width(400)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .vfov(20_f64)
+        .look_from(Point3::new(13_f64, 2_f64, 3_f64))
+        .look_at(Point3::zeroes());
+
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0_f64, -1e3, 0_f64),
+        1e3,
+        Arc::new(Lambertian::from(Arc::new(NoiseTexture::new(1_f64, rng)))),
+    )));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0_f64, 2_f64, 0_f64),
+        2_f64,
+        Arc::new(Lambertian::from(Arc::new(NoiseTexture::new(4_f64, rng)))),
+    )));
+
+    Ok(Scene::new(camera, world))
 }
 
-pub fn perlin_spheres() -> Result<()> {
+pub fn quads(_rng: &mut Rng) -> Result<Scene> {
     let mut world = HittableList::new();
-    let cam = Camera::new(
-        16_f64 / 9_f64,
-        400,
-        100,
-        50,
-        20_f64,
-        &Point3::new(13_f64, 2_f64, 3_f64),
-        &Point3::zeroes(),
-        &Vec3::new(0_f64, 1_f64, 0_f64),
-        0_f64,
-        1e1,
-    );
+
+    let left_red = Arc::new(Lambertian::new(Color::new(1_f64, 0.2, 0.2)));
+    let back_green = Arc::new(Lambertian::new(Color::new(0.2, 1_f64, 0.2)));
+    let right_blue = Arc::new(Lambertian::new(Color::new(0.2, 0.2, 1_f64)));
+    let upper_orange = Arc::new(Lambertian::new(Color::new(1_f64, 0.5, 0_f64)));
+    let lower_teal = Arc::new(Lambertian::new(Color::new(0.2, 0.8, 0.8)));
+
+    world.add(Arc::new(Quad::new(
+        Point3::new(-3_f64, -2_f64, 5_f64),
+        Vec3::new(0_f64, 0_f64, -4_f64),
+        Vec3::new(0_f64, 4_f64, 0_f64),
+        left_red,
+    )));
+    world.add(Arc::new(Quad::new(
+        Point3::new(-2_f64, -2_f64, 0_f64),
+        Vec3::new(4_f64, 0_f64, 0_f64),
+        Vec3::new(0_f64, 4_f64, 0_f64),
+        back_green,
+    )));
+    world.add(Arc::new(Quad::new(
+        Point3::new(3_f64, -2_f64, 1_f64),
+        Vec3::new(0_f64, 0_f64, 4_f64),
+        Vec3::new(0_f64, 4_f64, 0_f64),
+        right_blue,
+    )));
+    world.add(Arc::new(Quad::new(
+        Point3::new(-2_f64, 3_f64, 1_f64),
+        Vec3::new(4_f64, 0_f64, 0_f64),
+        Vec3::new(0_f64, 0_f64, 4_f64),
+        upper_orange,
+    )));
+    world.add(Arc::new(Quad::new(
+        Point3::new(-2_f64, -3_f64, 5_f64),
+        Vec3::new(4_f64, 0_f64, 0_f64),
+        Vec3::new(0_f64, 0_f64, -4_f64),
+        lower_teal,
+    )));
+
+    let camera = CameraBuilder::new()
+        .aspect_ratio(1_f64)
+        .image_width(400)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .vfov(80_f64)
+        .look_from(Point3::new(0_f64, 0_f64, 9_f64))
+        .look_at(Point3::zeroes());
+
+    Ok(Scene::new(camera, world))
+}
+
+pub fn simple_light(rng: &mut Rng) -> Result<Scene> {
+    let mut world = HittableList::new();
+    let pertext = Arc::new(NoiseTexture::new(4_f64, rng));
+    let difflight = Arc::new(DiffuseLight::new(Color::new(4_f64, 4_f64, 4_f64)));
 
     world.add(Arc::new(Sphere::new(
         Point3::new(0_f64, -1e3, 0_f64),
         1e3,
-        Arc::new(Lambertian::from(Arc::new(NoiseTexture::new(1_f64)))),
+        Arc::new(Lambertian::from(pertext.clone())),
     )));
     world.add(Arc::new(Sphere::new(
         Point3::new(0_f64, 2_f64, 0_f64),
         2_f64,
-        Arc::new(Lambertian::from(Arc::new(NoiseTexture::new(4_f64)))),
+        Arc::new(Lambertian::from(pertext)),
+    )));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0_f64, 7_f64, 0_f64),
+        2_f64,
+        difflight.clone(),
+    )));
+    world.add(Arc::new(Quad::new(
+        Point3::new(3_f64, 1_f64, -2_f64),
+        Vec3::new(2_f64, 0_f64, 0_f64),
+        Vec3::new(0_f64, 2_f64, 0_f64),
+        difflight,
+    )));
+
+    let camera = CameraBuilder::new()
+        .aspect_ratio(16_f64 / 9_f64)
+        .image_width(400)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .vfov(20_f64)
+        .look_from(Point3::new(26_f64, 3_f64, 6_f64))
+        .look_at(Point3::new(0_f64, 2_f64, 0_f64))
+        .background(Arc::new(SolidBackground::new(Color::zeroes())));
+
+    Ok(Scene::new(camera, world))
+}
+
+pub fn cornell_box(_rng: &mut Rng) -> Result<Scene> {
+    let mut world = HittableList::new();
+    let red = Arc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05)));
+    let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    let green = Arc::new(Lambertian::new(Color::new(0.12, 0.45, 0.15)));
+    let light = Arc::new(DiffuseLight::new(Color::new(15_f64, 15_f64, 15_f64)));
+
+    world.add(Arc::new(Quad::new(
+        Point3::new(555_f64, 0_f64, 0_f64),
+        Vec3::new(0_f64, 555_f64, 0_f64),
+        Vec3::new(0_f64, 0_f64, 555_f64),
+        green,
+    )));
+    world.add(Arc::new(Quad::new(
+        Point3::zeroes(),
+        Vec3::new(0_f64, 555_f64, 0_f64),
+        Vec3::new(0_f64, 0_f64, 555_f64),
+        red,
+    )));
+    world.add(Arc::new(Quad::new(
+        Point3::new(343_f64, 554_f64, 332_f64),
+        Vec3::new(-130_f64, 0_f64, 0_f64),
+        Vec3::new(0_f64, 0_f64, -105_f64),
+        light,
+    )));
+    world.add(Arc::new(Quad::new(
+        Point3::zeroes(),
+        Vec3::new(555_f64, 0_f64, 0_f64),
+        Vec3::new(0_f64, 0_f64, 555_f64),
+        white.clone(),
     )));
+    world.add(Arc::new(Quad::new(
+        Point3::new(555_f64, 555_f64, 555_f64),
+        Vec3::new(-555_f64, 0_f64, 0_f64),
+        Vec3::new(0_f64, 0_f64, -555_f64),
+        white.clone(),
+    )));
+    world.add(Arc::new(Quad::new(
+        Point3::new(0_f64, 0_f64, 555_f64),
+        Vec3::new(555_f64, 0_f64, 0_f64),
+        Vec3::new(0_f64, 555_f64, 0_f64),
+        white.clone(),
+    )));
+
+    let box1: Arc<dyn Hittable> = Arc::new(hittable::make_box(
+        &Point3::zeroes(),
+        &Point3::new(165_f64, 330_f64, 165_f64),
+        white.clone(),
+    ));
+    let box1: Arc<dyn Hittable> = Arc::new(RotateY::new(box1, 15_f64));
+    let box1 = Arc::new(Translate::new(box1, Vec3::new(265_f64, 0_f64, 295_f64)));
+
+    world.add(box1);
+
+    let box2: Arc<dyn Hittable> = Arc::new(hittable::make_box(
+        &Point3::zeroes(),
+        &Point3::new(165_f64, 165_f64, 165_f64),
+        white,
+    ));
+    let box2: Arc<dyn Hittable> = Arc::new(RotateY::new(box2, -18_f64));
+    let box2 = Arc::new(Translate::new(box2, Vec3::new(130_f64, 0_f64, 65_f64)));
+
+    world.add(box2);
+
+    let camera = CameraBuilder::new()
+        .aspect_ratio(1_f64)
+        .image_width(600)
+        .samples_per_pixel(200)
+        .max_depth(50)
+        .vfov(40_f64)
+        .look_from(Point3::new(278_f64, 278_f64, -800_f64))
+        .look_at(Point3::new(278_f64, 278_f64, 0_f64))
+        .background(Arc::new(SolidBackground::new(Color::zeroes())));
+
+    Ok(Scene::new(camera, world))
+}
+
+pub fn final_scene(rng: &mut Rng) -> Result<Scene> {
+    let mut boxes1 = HittableList::new();
+    let ground = Arc::new(Lambertian::new(Color::new(0.48, 0.83, 0.53)));
+    let boxes_per_side = 20;
+
+    for ij in 0..(boxes_per_side * boxes_per_side) {
+        let i = (ij / boxes_per_side) as f64;
+        let j = (ij % boxes_per_side) as f64;
+        let w = 1e2;
+        let x0 = -1e3 + i * w;
+        let z0 = -1e3 + j * w;
+        let y0 = 0_f64;
+        let x1 = x0 + w;
+        let y1 = rng.random_in_range(&Interval::new(1_f64, 101_f64));
+        let z1 = z0 + w;
+
+        boxes1.add(Arc::new(hittable::make_box(
+            &Point3::new(x0, y0, z0),
+            &Point3::new(x1, y1, z1),
+            ground.clone(),
+        )));
+    }
+
+    let mut world = HittableList::new();
+
+    world.add(Arc::new(BvhNode::build(&boxes1, BvhOptions::default())));
+
+    let light = Arc::new(DiffuseLight::new(Color::new(7_f64, 7_f64, 7_f64)));
+
+    world.add(Arc::new(Quad::new(
+        Point3::new(123_f64, 554_f64, 147_f64),
+        Vec3::new(300_f64, 0_f64, 0_f64),
+        Vec3::new(0_f64, 0_f64, 265_f64),
+        light,
+    )));
+
+    let center1 = Point3::new(400_f64, 400_f64, 200_f64);
+    let center2 = center1 + Vec3::new(30_f64, 0_f64, 0_f64);
+    let sphere_material = Arc::new(Lambertian::new(Color::new(0.7, 0.3, 0.1)));
+
+    world.add(Arc::new(Sphere::new_moving(
+        center1,
+        center2,
+        50_f64,
+        sphere_material,
+    )));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(260_f64, 150_f64, 45_f64),
+        50_f64,
+        Arc::new(Dielectric::new(1.5)),
+    )));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0_f64, 150_f64, 145_f64),
+        50_f64,
+        Arc::new(Metal::new(Color::new(0.8, 0.8, 0.9), 1_f64)),
+    )));
+
+    let boundary: Arc<dyn Hittable> = Arc::new(Sphere::new(
+        Point3::new(360_f64, 150_f64, 145_f64),
+        70_f64,
+        Arc::new(Dielectric::new(1.5)),
+    ));
+
+    world.add(boundary.clone());
+    world.add(Arc::new(ConstantMedium::new(
+        boundary,
+        0.2,
+        Color::new(0.2, 0.4, 0.9),
+    )));
+
+    let boundary: Arc<dyn Hittable> = Arc::new(Sphere::new(
+        Point3::zeroes(),
+        5e3,
+        Arc::new(Dielectric::new(1.5)),
+    ));
+
+    world.add(Arc::new(ConstantMedium::new(
+        boundary,
+        1e-4,
+        Color::new(1_f64, 1_f64, 1_f64),
+    )));
+
+    let emat = Arc::new(Lambertian::from(Arc::new(ImageTexture::new(
+        "earthmap.jpg",
+    )?)));
+
+    world.add(Arc::new(Sphere::new(
+        Point3::new(400_f64, 200_f64, 400_f64),
+        1e2,
+        emat,
+    )));
+
+    let pertext = Arc::new(NoiseTexture::new(0.2, rng));
+
+    world.add(Arc::new(Sphere::new(
+        Point3::new(220_f64, 280_f64, 300_f64),
+        80_f64,
+        Arc::new(Lambertian::from(pertext)),
+    )));
+
+    let mut boxes2 = HittableList::new();
+    let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+
+    for _ in 0..1000 {
+        boxes2.add(Arc::new(Sphere::new(
+            Point3::random_in_range(&Interval::new(0_f64, 165_f64), rng),
+            1e1,
+            white.clone(),
+        )));
+    }
+
+    let boxes2: Arc<dyn Hittable> = Arc::new(BvhNode::build(&boxes2, BvhOptions::default()));
+    let boxes2: Arc<dyn Hittable> = Arc::new(RotateY::new(boxes2, 15_f64));
+
+    world.add(Arc::new(Translate::new(
+        boxes2,
+        Vec3::new(-1e2, 270_f64, 395_f64),
+    )));
+
+    let camera = CameraBuilder::new()
+        .aspect_ratio(1_f64)
+        .image_width(800)
+        .samples_per_pixel(10000)
+        .max_depth(40)
+        .vfov(40_f64)
+        .look_from(Point3::new(478_f64, 278_f64, -600_f64))
+        .look_at(Point3::new(278_f64, 278_f64, 0_f64))
+        .background(Arc::new(SolidBackground::new(Color::zeroes())));
 
-    cam.render_png(&world, "images/hashed_random_texture.png")
+    Ok(Scene::new(camera, world))
 }